@@ -17,18 +17,47 @@ fn main() {
     let width = map.width;
     let height = map.height;
 
-    let cloud_layer = &map.layers[0];
-    let cloud_tiles = extract_tiles(&cloud_layer.tiles);
-
-    let background_layer = &map.layers[1];
-    let background_tiles = extract_tiles(&background_layer.tiles);
-
-    let foreground_layer = &map.layers[2];
-    let foreground_tiles = extract_tiles(&foreground_layer.tiles);
+    let layers = get_layers(&map.layers);
+    let layer_maps = layers.iter().map(|layer| &layer.tiles);
+    let layer_priorities = layers.iter().map(|layer| layer.priority);
+    let layer_parallax = layers.iter().map(|layer| layer.parallax);
+    let layer_drift_x = layers.iter().map(|layer| layer.drift_x);
+    let layer_drift_y = layers.iter().map(|layer| layer.drift_y);
+    let collision_layers = layers
+        .iter()
+        .enumerate()
+        .filter(|(_, layer)| layer.collides)
+        .map(|(i, _)| i);
 
     let (slimes_x, slimes_y) = get_spawn_locations(&map.object_groups[0], "Slime Spawn");
+    let slime_variants = get_spawn_variants(
+        &map.object_groups[0],
+        "Slime Spawn",
+        &["normal", "large", "small", "climbing"],
+    );
     let (bats_x, bats_y) = get_spawn_locations(&map.object_groups[0], "Bat Spawn");
+    let (bat_patrol_starts, bat_patrol_lengths, bat_waypoints_x, bat_waypoints_y) =
+        get_patrol_paths(&map.object_groups[0], "Bat Spawn");
     let (emus_x, emus_y) = get_spawn_locations(&map.object_groups[0], "Emu Spawn");
+    let (spitters_x, spitters_y) = get_spawn_locations(&map.object_groups[0], "Spitter Spawn");
+
+    let (
+        scroll_triggers_x,
+        scroll_triggers_y,
+        scroll_trigger_speeds,
+        scroll_trigger_directions,
+        scroll_trigger_until_clear,
+    ) = get_scroll_triggers(&map.object_groups[0]);
+
+    let boss_arena = get_boss_arena(&map.object_groups[0]);
+    let (arena_x, arena_y, arena_width, arena_height) = (
+        boss_arena.x as u16,
+        boss_arena.y as u16,
+        boss_arena.width as u16,
+        boss_arena.height as u16,
+    );
+    let (boss_spawn_x, boss_spawn_y, boss_waypoints_x, boss_waypoints_y) =
+        get_boss_waypoints(&map.object_groups[0]);
 
     let mut tile_types = HashMap::new();
 
@@ -38,25 +67,59 @@ fn main() {
         }
     }
 
+    // the font for the menus and other text is loaded straight after the level's tiles, at tile 448
+    // in src/text.rs
+    assert!(
+        map.tilesets[0].tilecount.unwrap() < 448,
+        "the level's tileset must have fewer than 448 tiles to leave room for the font"
+    );
+
     let tile_types =
         (0..map.tilesets[0].tilecount.unwrap()).map(|id| tile_types.get(&(id + 1)).unwrap_or(&0));
 
     let output = quote! {
-        pub const CLOUD_MAP: &[u16] = &[#(#cloud_tiles),*];
-        pub const BACKGROUND_MAP: &[u16] = &[#(#background_tiles),*];
-        pub const FOREGROUND_MAP: &[u16] = &[#(#foreground_tiles),*];
+        pub const LAYER_MAPS: &[&[u16]] = &[#(&[#(#layer_maps),*]),*];
+        pub const LAYER_PRIORITIES: &[u8] = &[#(#layer_priorities),*];
+        pub const LAYER_PARALLAX: &[u16] = &[#(#layer_parallax),*];
+        pub const LAYER_DRIFT_X: &[i16] = &[#(#layer_drift_x),*];
+        pub const LAYER_DRIFT_Y: &[i16] = &[#(#layer_drift_y),*];
+        pub const COLLISION_LAYERS: &[usize] = &[#(#collision_layers),*];
         pub const WIDTH: u32 = #width;
         pub const HEIGHT: u32 = #height;
 
         pub const SLIME_SPAWNS_X: &[u16] = &[#(#slimes_x),*];
         pub const SLIME_SPAWNS_Y: &[u16] = &[#(#slimes_y),*];
+        pub const SLIME_VARIANTS: &[u8] = &[#(#slime_variants),*];
 
         pub const BAT_SPAWNS_X: &[u16] = &[#(#bats_x),*];
         pub const BAT_SPAWNS_Y: &[u16] = &[#(#bats_y),*];
+        pub const BAT_PATROL_STARTS: &[u16] = &[#(#bat_patrol_starts),*];
+        pub const BAT_PATROL_LENGTHS: &[u16] = &[#(#bat_patrol_lengths),*];
+        pub const BAT_WAYPOINTS_X: &[u16] = &[#(#bat_waypoints_x),*];
+        pub const BAT_WAYPOINTS_Y: &[u16] = &[#(#bat_waypoints_y),*];
 
         pub const EMU_SPAWNS_X: &[u16] = &[#(#emus_x),*];
         pub const EMU_SPAWNS_Y: &[u16] = &[#(#emus_y),*];
 
+        pub const SPITTER_SPAWNS_X: &[u16] = &[#(#spitters_x),*];
+        pub const SPITTER_SPAWNS_Y: &[u16] = &[#(#spitters_y),*];
+
+        pub const SCROLL_TRIGGERS_X: &[u16] = &[#(#scroll_triggers_x),*];
+        pub const SCROLL_TRIGGERS_Y: &[u16] = &[#(#scroll_triggers_y),*];
+        pub const SCROLL_TRIGGER_SPEEDS: &[u16] = &[#(#scroll_trigger_speeds),*];
+        pub const SCROLL_TRIGGER_DIRECTIONS: &[u8] = &[#(#scroll_trigger_directions),*];
+        pub const SCROLL_TRIGGER_UNTIL_CLEAR: &[bool] = &[#(#scroll_trigger_until_clear),*];
+
+        pub const BOSS_ARENA_X: u16 = #arena_x;
+        pub const BOSS_ARENA_Y: u16 = #arena_y;
+        pub const BOSS_ARENA_WIDTH: u16 = #arena_width;
+        pub const BOSS_ARENA_HEIGHT: u16 = #arena_height;
+
+        pub const BOSS_SPAWN_X: u16 = #boss_spawn_x;
+        pub const BOSS_SPAWN_Y: u16 = #boss_spawn_y;
+        pub const BOSS_WAYPOINTS_X: &[u16] = &[#(#boss_waypoints_x),*];
+        pub const BOSS_WAYPOINTS_Y: &[u16] = &[#(#boss_waypoints_y),*];
+
         pub const TILE_TYPES: &[u8] = &[#(#tile_types),*];
     };
 
@@ -65,6 +128,410 @@ fn main() {
     let mut writer = BufWriter::new(output_file);
 
     write!(&mut writer, "{}", output).unwrap();
+
+    let sound_manifest = "sfx/sounds.txt";
+    println!("cargo:rerun-if-changed={}", sound_manifest);
+
+    let sounds = get_sounds(Path::new(sound_manifest));
+    let sound_ids = sounds
+        .iter()
+        .map(|sound| quote::format_ident!("{}", sound.id));
+    let sound_bank = sounds.iter().map(|sound| {
+        let samples = sound.samples.iter().map(|sample| format!("sfx/{}", sample));
+        let (volume, playback, pitch_jitter, priority, max_instances, cooldown, duck) = (
+            sound.volume,
+            sound.playback,
+            sound.pitch_jitter,
+            quote::format_ident!("{}", sound.priority),
+            sound.max_instances,
+            sound.cooldown,
+            sound.duck,
+        );
+
+        quote! {
+            Sound {
+                variants: &[#(agb::include_wav!(#samples)),*],
+                volume: #volume,
+                playback: #playback,
+                pitch_jitter: #pitch_jitter,
+                priority: Priority::#priority,
+                max_instances: #max_instances,
+                cooldown: #cooldown,
+                duck: #duck,
+            }
+        }
+    });
+
+    let output = quote! {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        pub enum SoundId {
+            #(#sound_ids),*
+        }
+
+        const SOUNDS: &[Sound] = &[#(#sound_bank),*];
+    };
+
+    let output_file = File::create(format!("{}/sounds.rs", out_dir))
+        .expect("failed to open sounds.rs file for writing");
+    let mut writer = BufWriter::new(output_file);
+
+    write!(&mut writer, "{}", output).unwrap();
+
+    println!("cargo:rerun-if-changed=sfx");
+
+    let mut module_paths: Vec<_> = std::fs::read_dir("sfx")
+        .expect("failed to read the sfx folder")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .map_or(false, |extension| extension == "mod")
+        })
+        .collect();
+    module_paths.sort();
+
+    let modules = module_paths.iter().map(|path| {
+        println!("cargo:rerun-if-changed={}", path.display());
+
+        let module = get_module(path);
+        let name = quote::format_ident!("{}", module.name);
+        let instruments = module.instruments.iter().map(|instrument| {
+            let (attack, sustain, volume, rate) = (
+                &instrument.attack,
+                &instrument.sustain,
+                instrument.volume,
+                instrument.rate,
+            );
+
+            quote! {
+                Instrument {
+                    attack: &[#(#attack),*],
+                    sustain: &[#(#sustain),*],
+                    volume: #volume,
+                    rate: #rate,
+                }
+            }
+        });
+        let order = &module.order;
+        let patterns = module.patterns.iter().map(|pattern| {
+            let rows = pattern.iter().map(|row| {
+                let notes = row.iter().map(|note| {
+                    let (period, instrument, effect, parameter) =
+                        (note.period, note.instrument, note.effect, note.parameter);
+
+                    quote! {
+                        Note {
+                            period: #period,
+                            instrument: #instrument,
+                            effect: #effect,
+                            parameter: #parameter,
+                        }
+                    }
+                });
+
+                quote! { [#(#notes),*] }
+            });
+
+            quote! { [#(#rows),*] }
+        });
+
+        quote! {
+            pub const #name: Module = Module {
+                instruments: &[#(#instruments),*],
+                order: &[#(#order),*],
+                patterns: &[#(#patterns),*],
+            };
+        }
+    });
+
+    let output = quote! {
+        #(#modules)*
+    };
+
+    let output_file = File::create(format!("{}/modules.rs", out_dir))
+        .expect("failed to open modules.rs file for writing");
+    let mut writer = BufWriter::new(output_file);
+
+    write!(&mut writer, "{}", output).unwrap();
+}
+
+struct ModuleData {
+    name: String,
+    instruments: Vec<InstrumentData>,
+    order: Vec<u8>,
+    patterns: Vec<Vec<[NoteData; 4]>>,
+}
+
+struct InstrumentData {
+    attack: Vec<u8>,
+    sustain: Vec<u8>,
+    volume: u8,
+    rate: u32,
+}
+
+struct NoteData {
+    period: u16,
+    instrument: u8,
+    effect: u8,
+    parameter: u8,
+}
+
+// Reads a 4 channel ProTracker module, named after the file in SCREAMING_SNAKE_CASE. Samples with
+// a loop are split into the part before the loop and the loop itself, since the mixer can only
+// loop a whole sample. The samples are already signed 8 bit, which is what the mixer plays.
+fn get_module(path: &Path) -> ModuleData {
+    let data = std::fs::read(path).expect("failed to read module");
+    let word = |offset: usize| u16::from_be_bytes([data[offset], data[offset + 1]]) as usize;
+
+    assert!(
+        data.len() >= 1084 && &data[1080..1084] == b"M.K.",
+        "{} must be a 4 channel ProTracker module",
+        path.display()
+    );
+
+    let song_length = data[950] as usize;
+    let order = data[952..952 + song_length].to_vec();
+    let pattern_count = data[952..1080].iter().max().copied().unwrap_or(0) as usize + 1;
+
+    let patterns: Vec<Vec<[NoteData; 4]>> = data[1084..1084 + pattern_count * 1024]
+        .chunks(1024)
+        .map(|pattern| {
+            pattern
+                .chunks(16)
+                .map(|row| {
+                    let note = |channel: usize| {
+                        let note = &row[channel * 4..channel * 4 + 4];
+                        NoteData {
+                            period: (((note[0] & 0x0f) as u16) << 8) | note[1] as u16,
+                            instrument: (note[0] & 0xf0) | (note[2] >> 4),
+                            effect: note[2] & 0x0f,
+                            parameter: note[3],
+                        }
+                    };
+
+                    [note(0), note(1), note(2), note(3)]
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut sample_start = 1084 + pattern_count * 1024;
+    let instruments = (0..31)
+        .map(|i| {
+            let header = 20 + i * 30;
+            let length = word(header + 22) * 2;
+            let finetune = ((data[header + 24] << 4) as i8 >> 4) as f64;
+            let volume = data[header + 25].min(64);
+            let loop_start = word(header + 26) * 2;
+            let loop_length = word(header + 28) * 2;
+
+            let sample = &data[sample_start..(sample_start + length).min(data.len())];
+            sample_start += length;
+
+            let (attack, sustain) = if loop_length > 2 {
+                let loop_end = (loop_start + loop_length).min(sample.len());
+                (
+                    sample[..loop_start].to_vec(),
+                    sample[loop_start..loop_end].to_vec(),
+                )
+            } else {
+                (sample.to_vec(), Vec::new())
+            };
+
+            // the Amiga plays a sample at 7093789.2 / (2 * period) Hz, and the mixer runs at
+            // 18157Hz. each step of finetune is an eighth of a semitone.
+            let rate = 7093789.2 / 2.0 / 18157.0 * 256.0 * 2f64.powf(finetune / 96.0);
+
+            InstrumentData {
+                attack,
+                sustain,
+                volume,
+                rate: rate.round() as u32,
+            }
+        })
+        .collect();
+
+    let name = path
+        .file_stem()
+        .unwrap()
+        .to_string_lossy()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    ModuleData {
+        name,
+        instruments,
+        order,
+        patterns,
+    }
+}
+
+struct SoundEntry {
+    id: String,
+    samples: Vec<String>,
+    volume: i16,
+    playback: usize,
+    pitch_jitter: usize,
+    priority: &'static str,
+    max_instances: usize,
+    cooldown: u16,
+    duck: bool,
+}
+
+// Reads the sound bank manifest, which is described at the top of the file. The volume is
+// returned as a fixed point number with 4 fractional bits and the playback speeds with 8, to match
+// what the mixer uses.
+fn get_sounds(path: &Path) -> Vec<SoundEntry> {
+    let manifest = std::fs::read_to_string(path).expect("failed to read the sound manifest");
+    let mut sounds: Vec<SoundEntry> = Vec::new();
+
+    for line in manifest.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            sounds.push(SoundEntry {
+                // the enum variant is the name in CamelCase
+                id: name
+                    .split('_')
+                    .map(|word| word[..1].to_uppercase() + &word[1..])
+                    .collect(),
+                samples: Vec::new(),
+                volume: 1 << 4,
+                playback: 1 << 8,
+                pitch_jitter: 0,
+                priority: "Enemy",
+                max_instances: 1,
+                cooldown: 1,
+                duck: false,
+            });
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .unwrap_or_else(|| panic!("expected key = value in the sound manifest, got {}", line));
+        let sound = sounds
+            .last_mut()
+            .unwrap_or_else(|| panic!("{} must be inside a [sound] section", key));
+
+        let float = || -> f32 {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("{} must be a number", key))
+        };
+        let int = || -> u16 {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("{} must be a whole number", key))
+        };
+
+        match key {
+            "samples" => sound.samples = value.split_whitespace().map(String::from).collect(),
+            "volume" => sound.volume = (float() * 16.0) as i16,
+            "playback" => sound.playback = (float() * 256.0) as usize,
+            "pitch_jitter" => sound.pitch_jitter = (float() * 256.0) as usize,
+            "priority" => {
+                sound.priority = match value {
+                    "ambient" => "Ambient",
+                    "enemy" => "Enemy",
+                    "important" => "Important",
+                    _ => panic!(
+                        "priority must be ambient, enemy or important, got {}",
+                        value
+                    ),
+                }
+            }
+            "max_instances" => sound.max_instances = int() as usize,
+            "cooldown" => sound.cooldown = int(),
+            "duck" => {
+                sound.duck = value
+                    .parse()
+                    .unwrap_or_else(|_| panic!("duck must be true or false"))
+            }
+            _ => panic!("unknown sound property {}", key),
+        }
+    }
+
+    for sound in sounds.iter() {
+        assert!(
+            !sound.samples.is_empty(),
+            "sound {} has no samples",
+            sound.id
+        );
+        assert!(
+            sound.pitch_jitter < sound.playback,
+            "sound {} has more pitch jitter than playback speed",
+            sound.id
+        );
+    }
+
+    sounds
+}
+
+struct Layer {
+    tiles: Vec<u16>,
+    priority: u8,
+    parallax: u16,
+    drift_x: i16,
+    drift_y: i16,
+    collides: bool,
+}
+
+// Each tile layer becomes a background, drawn bottom to top in the order they appear in Tiled.
+// Layers can set their `priority` (default 0), a `parallax` factor for how far they move compared
+// to the camera (default 1), how many pixels they `drift_x` and `drift_y` each frame on their own
+// (default 0), and whether the player `collides` with them (default false). The parallax and
+// drift are returned as fixed point numbers with 8 fractional bits.
+fn get_layers(layers: &[tiled::Layer]) -> Vec<Layer> {
+    // if the map leaves a background free then the menus and other text are drawn on it
+    assert!(
+        layers.len() <= 4,
+        "only 4 backgrounds are available for the map"
+    );
+
+    layers
+        .iter()
+        .map(|layer| {
+            let float_property = |name: &str, default: f32| match layer.properties.get(name) {
+                Some(tiled::PropertyValue::FloatValue(value)) => *value,
+                Some(_) => panic!("layer {} {} must be a float", layer.name, name),
+                None => default,
+            };
+
+            let priority = match layer.properties.get("priority") {
+                Some(tiled::PropertyValue::IntValue(priority @ 0..=3)) => *priority as u8,
+                Some(_) => panic!("layer {} priority must be an int from 0 to 3", layer.name),
+                None => 0,
+            };
+
+            let collides = match layer.properties.get("collides") {
+                Some(tiled::PropertyValue::BoolValue(collides)) => *collides,
+                Some(_) => panic!("layer {} collides must be a bool", layer.name),
+                None => false,
+            };
+
+            Layer {
+                tiles: extract_tiles(&layer.tiles).collect(),
+                priority,
+                parallax: (float_property("parallax", 1.0) * 256.0) as u16,
+                drift_x: (float_property("drift_x", 0.0) * 256.0) as i16,
+                drift_y: (float_property("drift_y", 0.0) * 256.0) as i16,
+                collides,
+            }
+        })
+        .collect()
 }
 
 fn extract_tiles<'a>(layer: &'a tiled::LayerData) -> impl Iterator<Item = u16> + 'a {
@@ -105,3 +572,175 @@ fn get_spawn_locations<'a>(
 
     return (xs.into_iter(), ys.into_iter());
 }
+
+// Reads the optional `variant` property of each spawn as an index into `variants`, defaulting
+// to the first one. Returned in the same order as get_spawn_locations.
+fn get_spawn_variants(
+    object_group: &tiled::ObjectGroup,
+    enemy_type: &str,
+    variants: &[&str],
+) -> Vec<u8> {
+    let mut spawns = object_group
+        .objects
+        .iter()
+        .filter(|object| &object.obj_type == enemy_type)
+        .map(|object| {
+            let variant = match object.properties.get("variant") {
+                Some(tiled::PropertyValue::StringValue(variant)) => variants
+                    .iter()
+                    .position(|v| v == variant)
+                    .unwrap_or_else(|| panic!("unknown {} variant {}", enemy_type, variant)),
+                Some(_) => panic!("{} variant must be a string", enemy_type),
+                None => 0,
+            };
+
+            (object.x as u16, variant as u8)
+        })
+        .collect::<Vec<_>>();
+
+    spawns.sort_by(|a, b| a.0.cmp(&b.0));
+
+    spawns.into_iter().map(|spawn| spawn.1).collect()
+}
+
+// Spawns drawn as polylines rather than points describe a patrol path starting at the spawn.
+// The paths are returned in the same order as get_spawn_locations, flattened into a single
+// list of waypoints with a start index and length for each spawn.
+fn get_patrol_paths(
+    object_group: &tiled::ObjectGroup,
+    enemy_type: &str,
+) -> (Vec<u16>, Vec<u16>, Vec<u16>, Vec<u16>) {
+    let mut spawns = object_group
+        .objects
+        .iter()
+        .filter(|object| &object.obj_type == enemy_type)
+        .map(|object| {
+            let points = match &object.shape {
+                tiled::ObjectShape::Polyline { points }
+                | tiled::ObjectShape::Polygon { points } => points
+                    .iter()
+                    .map(|(x, y)| ((object.x + x) as u16, (object.y + y) as u16))
+                    .collect(),
+                _ => vec![],
+            };
+
+            (object.x as u16, points)
+        })
+        .collect::<Vec<_>>();
+
+    spawns.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut starts = vec![];
+    let mut lengths = vec![];
+    let mut xs = vec![];
+    let mut ys = vec![];
+
+    for (_, points) in spawns {
+        starts.push(xs.len() as u16);
+        lengths.push(points.len() as u16);
+
+        for (x, y) in points {
+            xs.push(x);
+            ys.push(y);
+        }
+    }
+
+    (starts, lengths, xs, ys)
+}
+
+// Scroll triggers change the autoscroll once the middle of the screen passes them. Each one sets
+// the `speed` in pixels per frame (default 0.125) and the `direction` (default right), and can
+// stop the scrolling `until_clear` of enemies. The speed is returned as a fixed point number with
+// 8 fractional bits and the direction as an index into right, left, down, up.
+fn get_scroll_triggers(
+    object_group: &tiled::ObjectGroup,
+) -> (Vec<u16>, Vec<u16>, Vec<u16>, Vec<u8>, Vec<bool>) {
+    let mut triggers = object_group
+        .objects
+        .iter()
+        .filter(|object| object.obj_type == "Scroll Trigger")
+        .map(|object| {
+            let speed = match object.properties.get("speed") {
+                Some(tiled::PropertyValue::FloatValue(speed)) => *speed,
+                Some(_) => panic!("scroll trigger speed must be a float"),
+                None => 0.125,
+            };
+
+            let direction = match object.properties.get("direction") {
+                Some(tiled::PropertyValue::StringValue(direction)) => {
+                    ["right", "left", "down", "up"]
+                        .iter()
+                        .position(|d| d == direction)
+                        .unwrap_or_else(|| panic!("unknown scroll direction {}", direction))
+                }
+                Some(_) => panic!("scroll trigger direction must be a string"),
+                None => 0,
+            };
+
+            let until_clear = match object.properties.get("until_clear") {
+                Some(tiled::PropertyValue::BoolValue(until_clear)) => *until_clear,
+                Some(_) => panic!("scroll trigger until_clear must be a bool"),
+                None => false,
+            };
+
+            (
+                object.x as u16,
+                object.y as u16,
+                (speed * 256.0) as u16,
+                direction as u8,
+                until_clear,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    triggers.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut xs = vec![];
+    let mut ys = vec![];
+    let mut speeds = vec![];
+    let mut directions = vec![];
+    let mut until_clear = vec![];
+
+    for (x, y, speed, direction, clear) in triggers {
+        xs.push(x);
+        ys.push(y);
+        speeds.push(speed);
+        directions.push(direction);
+        until_clear.push(clear);
+    }
+
+    (xs, ys, speeds, directions, until_clear)
+}
+
+// The boss arena is a rectangle covering the screen the camera locks to during the boss fight
+fn get_boss_arena(object_group: &tiled::ObjectGroup) -> &tiled::Object {
+    object_group
+        .objects
+        .iter()
+        .find(|object| object.obj_type == "Boss Arena")
+        .expect("map must contain a Boss Arena")
+}
+
+// The boss waypoint named "spawn" is where the boss appears, and the rest are the places it moves
+// between during the fight
+fn get_boss_waypoints(object_group: &tiled::ObjectGroup) -> (u16, u16, Vec<u16>, Vec<u16>) {
+    let spawn = object_group
+        .objects
+        .iter()
+        .find(|object| object.obj_type == "Boss Waypoint" && object.name == "spawn")
+        .expect("map must contain a Boss Waypoint named spawn");
+
+    let (xs, ys): (Vec<u16>, Vec<u16>) = object_group
+        .objects
+        .iter()
+        .filter(|object| object.obj_type == "Boss Waypoint" && object.name != "spawn")
+        .map(|object| (object.x as u16, object.y as u16))
+        .unzip();
+
+    assert!(
+        !xs.is_empty(),
+        "map must contain at least one Boss Waypoint other than spawn"
+    );
+
+    (spawn.x as u16, spawn.y as u16, xs, ys)
+}
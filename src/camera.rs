@@ -0,0 +1,170 @@
+use super::Number;
+use agb::{
+    display::{HEIGHT, WIDTH},
+    number::{Rect, Vector2D},
+};
+
+pub enum CameraMode {
+    // moves by a fixed amount every frame regardless of what the player does
+    Autoscroll(Vector2D<Number>),
+    // keeps the target on screen, only moving once it leaves the dead zone
+    Follow,
+    // stays fixed with the top left of the screen at the given position
+    Locked(Vector2D<Number>),
+    // moves towards a point at the given speed, then locks there
+    Panning(Vector2D<Number>, Number),
+}
+
+pub struct Camera {
+    position: Vector2D<Number>,
+    mode: CameraMode,
+    // the area the top left of the screen is allowed to be in
+    bounds: Rect<Number>,
+    // how far the target can get from the centre of the screen before the camera follows
+    dead_zone: Vector2D<Number>,
+    // how far ahead of a moving target the camera tries to look
+    look_ahead: Number,
+}
+
+impl Camera {
+    pub fn new(position: Vector2D<Number>, bounds: Rect<Number>) -> Self {
+        Self {
+            position,
+            mode: CameraMode::Locked(position),
+            bounds,
+            dead_zone: (16, 24).into(),
+            look_ahead: 24.into(),
+        }
+    }
+
+    // bounds which keep an 8 pixel border of the map off screen
+    pub fn bounds_for_map(width: u32, height: u32) -> Rect<Number> {
+        let map_size: Vector2D<Number> = (width as i32 * 8, height as i32 * 8).into();
+        let screen_size: Vector2D<Number> = (WIDTH, HEIGHT).into();
+
+        Rect::new((8, 8).into(), map_size - screen_size - (16, 16).into())
+    }
+
+    pub fn position(&self) -> Vector2D<Number> {
+        self.position
+    }
+
+    pub fn autoscroll(&mut self, velocity: Vector2D<Number>) {
+        self.mode = CameraMode::Autoscroll(velocity);
+    }
+
+    pub fn follow(&mut self) {
+        self.mode = CameraMode::Follow;
+    }
+
+    pub fn pan_to(&mut self, position: Vector2D<Number>, speed: Number) {
+        self.mode = CameraMode::Panning(position, speed);
+    }
+
+    pub fn is_at_left_edge(&self) -> bool {
+        self.position.x <= self.bounds.position.x
+    }
+
+    pub fn can_see(&self, point: Vector2D<Number>) -> bool {
+        let relative = point - self.position;
+        relative.x >= 0.into()
+            && relative.x < WIDTH.into()
+            && relative.y >= 0.into()
+            && relative.y < HEIGHT.into()
+    }
+
+    // whether the middle of the screen has gone past the point in the direction of the autoscroll
+    pub fn has_scrolled_past(&self, point: Vector2D<Number>) -> bool {
+        let velocity = match &self.mode {
+            CameraMode::Autoscroll(velocity) => *velocity,
+            _ => return false,
+        };
+
+        let centre = self.position + (WIDTH / 2, HEIGHT / 2).into();
+
+        (velocity.x > 0.into() && centre.x >= point.x)
+            || (velocity.x < 0.into() && centre.x <= point.x)
+            || (velocity.y > 0.into() && centre.y >= point.y)
+            || (velocity.y < 0.into() && centre.y <= point.y)
+    }
+
+    // whether the point has gone off the edge of the screen that the camera is moving away from.
+    // anything other than autoscrolling counts as moving right.
+    pub fn is_behind(&self, point: Vector2D<Number>) -> bool {
+        let velocity = match &self.mode {
+            CameraMode::Autoscroll(velocity) => *velocity,
+            _ => (1, 0).into(),
+        };
+
+        let relative = point - self.position;
+
+        (velocity.x > 0.into() && relative.x < (-8).into())
+            || (velocity.x < 0.into() && relative.x > (WIDTH + 8).into())
+            || (velocity.y > 0.into() && relative.y < (-8).into())
+            || (velocity.y < 0.into() && relative.y > (HEIGHT + 8).into())
+    }
+
+    pub fn update(&mut self, target: Vector2D<Number>, target_velocity: Vector2D<Number>) {
+        match &mut self.mode {
+            CameraMode::Autoscroll(velocity) => {
+                self.position += *velocity;
+            }
+            CameraMode::Follow => {
+                let look_ahead = self.look_ahead * target_velocity.x.to_raw().signum();
+                let focus = target + Vector2D::new(look_ahead, 0.into());
+                let screen_centre = self.position + (WIDTH / 2, HEIGHT / 2).into();
+                let difference = focus - screen_centre;
+
+                let outside_dead_zone: Vector2D<Number> = (
+                    outside_of(difference.x, self.dead_zone.x),
+                    outside_of(difference.y, self.dead_zone.y),
+                )
+                    .into();
+
+                self.position += outside_dead_zone / 8;
+            }
+            CameraMode::Locked(position) => {
+                self.position = *position;
+            }
+            CameraMode::Panning(target, speed) => {
+                let difference = *target - self.position;
+                if difference.manhattan_distance() <= *speed {
+                    self.position = *target;
+                    self.mode = CameraMode::Locked(self.position);
+                } else {
+                    self.position += difference.normalise() * *speed;
+                }
+            }
+        }
+
+        self.clamp_to_bounds();
+    }
+
+    fn clamp_to_bounds(&mut self) {
+        let min = self.bounds.position;
+        let max = self.bounds.position + self.bounds.size;
+
+        if self.position.x < min.x {
+            self.position.x = min.x;
+        } else if self.position.x > max.x {
+            self.position.x = max.x;
+        }
+
+        if self.position.y < min.y {
+            self.position.y = min.y;
+        } else if self.position.y > max.y {
+            self.position.y = max.y;
+        }
+    }
+}
+
+// how far outside of the range -extent..extent the value is
+fn outside_of(value: Number, extent: Number) -> Number {
+    if value > extent {
+        value - extent
+    } else if value < -extent {
+        value + extent
+    } else {
+        0.into()
+    }
+}
@@ -0,0 +1,134 @@
+// The colour maths behind the palette effects. It doesn't use agb or anything else from the game,
+// so unlike the rest of the crate its tests can be built and run on the computer:
+//
+//     rustc --edition 2018 --test src/colour.rs --out-dir target/colour && target/colour/colour
+
+// not every curve is used by the game at the moment, but they are all covered by the tests
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    // jumps straight to the destination once the time is up
+    Step,
+}
+
+impl Easing {
+    const ONE: i32 = 256;
+
+    // how far through the transition to be, from 0 to Easing::ONE
+    fn progress(self, time_so_far: u16, total_time: u16) -> i32 {
+        let total_time = total_time.max(1) as i32;
+        let t = time_so_far.min(total_time as u16) as i32 * Self::ONE / total_time;
+        let one = Self::ONE;
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t / one,
+            Easing::EaseOut => one - (one - t) * (one - t) / one,
+            Easing::EaseInOut => {
+                if t < one / 2 {
+                    2 * t * t / one
+                } else {
+                    one - 2 * (one - t) * (one - t) / one
+                }
+            }
+            Easing::Step => {
+                if t == one {
+                    one
+                } else {
+                    0
+                }
+            }
+        }
+    }
+}
+
+// Interpolates each 5 bit component separately in signed arithmetic, so colours can fade in any
+// direction without wrapping around.
+pub fn interpolate_colour(
+    initial: u16,
+    destination: u16,
+    time_so_far: u16,
+    total_time: u16,
+    easing: Easing,
+) -> u16 {
+    const MASK: i32 = 0b11111;
+    let progress = easing.progress(time_so_far, total_time);
+
+    (0..3).fold(0, |colour, i| {
+        let a = (initial as i32 >> (i * 5)) & MASK;
+        let b = (destination as i32 >> (i * 5)) & MASK;
+        let c = a + (b - a) * progress / Easing::ONE;
+
+        colour | ((c.clamp(0, MASK) as u16) << (i * 5))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EASINGS: [Easing; 5] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::Step,
+    ];
+
+    // runs every pair of values for each of red, green and blue through every easing. the other two
+    // components are held in the middle, so any carry or borrow between components shows up.
+    #[test]
+    fn interpolation_stays_between_the_endpoints() {
+        const TOTAL_TIME: u16 = 30;
+
+        for &easing in EASINGS.iter() {
+            for shift in [0, 5, 10] {
+                let others = 0x4210 & !(0b11111 << shift);
+
+                for a in 0..32 {
+                    for b in 0..32 {
+                        let initial = others | (a << shift);
+                        let destination = others | (b << shift);
+
+                        for time in 0..=TOTAL_TIME {
+                            let colour =
+                                interpolate_colour(initial, destination, time, TOTAL_TIME, easing);
+                            let c = (colour >> shift) & 0b11111;
+
+                            assert_eq!(colour & !(0b11111 << shift), others);
+                            assert!(c >= a.min(b) && c <= a.max(b));
+
+                            if time == 0 {
+                                assert_eq!(c, a);
+                            }
+                            if time == TOTAL_TIME {
+                                assert_eq!(c, b);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn progress_goes_from_zero_to_one() {
+        for &easing in EASINGS.iter() {
+            for total_time in 1..=64 {
+                let mut last = 0;
+                for time in 0..=total_time + 1 {
+                    let progress = easing.progress(time, total_time);
+                    assert!(progress >= last && progress <= Easing::ONE);
+                    last = progress;
+                }
+
+                assert_eq!(easing.progress(0, total_time), 0);
+                assert_eq!(easing.progress(total_time, total_time), Easing::ONE);
+            }
+        }
+    }
+}
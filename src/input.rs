@@ -0,0 +1,140 @@
+use agb::input::{Button, ButtonController, Tri};
+
+use crate::settings::Settings;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Left,
+    Right,
+    Up,
+    Down,
+    Jump,
+    Attack,
+    Pause,
+    ToggleShake,
+    // moving around the menus
+    Confirm,
+    Back,
+}
+
+impl Action {
+    const ALL: [Action; 10] = [
+        Action::Left,
+        Action::Right,
+        Action::Up,
+        Action::Down,
+        Action::Jump,
+        Action::Attack,
+        Action::Pause,
+        Action::ToggleShake,
+        Action::Confirm,
+        Action::Back,
+    ];
+
+    fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+// which button performs each action, indexed by the action
+#[derive(Clone, Copy)]
+pub struct Bindings {
+    buttons: [Button; Action::ALL.len()],
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            buttons: [
+                Button::LEFT,
+                Button::RIGHT,
+                Button::UP,
+                Button::DOWN,
+                Button::A,
+                Button::B,
+                Button::START,
+                Button::SELECT,
+                Button::A,
+                Button::B,
+            ],
+        }
+    }
+}
+
+impl Bindings {
+    pub fn new(settings: &Settings) -> Self {
+        let mut bindings = Self::default();
+        bindings.bind(Action::Jump, settings.jump());
+        bindings.bind(Action::Attack, settings.attack());
+        bindings
+    }
+
+    pub fn bind(&mut self, action: Action, button: Button) {
+        self.buttons[action as usize] = button;
+    }
+}
+
+// Reads the buttons once a frame and turns them into the actions they are bound to, so nothing
+// else needs to know which button does what. The actions are kept as bit sets, one bit for each
+// action, for the frame just gone and the one before it.
+pub struct Input {
+    buttons: ButtonController,
+    bindings: Bindings,
+    current: u16,
+    previous: u16,
+}
+
+impl Input {
+    pub fn new(bindings: Bindings) -> Self {
+        Self {
+            buttons: ButtonController::new(),
+            bindings,
+            current: 0,
+            previous: 0,
+        }
+    }
+
+    pub fn set_bindings(&mut self, bindings: Bindings) {
+        self.bindings = bindings;
+    }
+
+    pub fn update(&mut self) {
+        self.buttons.update();
+
+        self.previous = self.current;
+        self.current = Action::ALL
+            .iter()
+            .filter(|&&action| {
+                self.buttons
+                    .is_pressed(self.bindings.buttons[action as usize])
+            })
+            .fold(0, |actions, action| actions | action.bit());
+    }
+
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.current & action.bit() != 0
+    }
+
+    pub fn is_just_pressed(&self, action: Action) -> bool {
+        self.current & !self.previous & action.bit() != 0
+    }
+
+    // nothing reacts to letting go of a button at the moment, but this rounds out the queries for
+    // when something does
+    #[allow(dead_code)]
+    pub fn is_just_released(&self, action: Action) -> bool {
+        !self.current & self.previous & action.bit() != 0
+    }
+
+    // which way left and right are being held, or neither if both are
+    pub fn x_tri(&self) -> Tri {
+        match (
+            self.is_pressed(Action::Left),
+            self.is_pressed(Action::Right),
+        ) {
+            (true, false) => Tri::Negative,
+            (false, true) => Tri::Positive,
+            _ => Tri::Zero,
+        }
+    }
+}
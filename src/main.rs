@@ -4,78 +4,178 @@
 extern crate agb;
 extern crate alloc;
 
+mod camera;
+mod colour;
+mod input;
+mod menu;
+mod palette;
 mod rng;
+mod settings;
 mod sfx;
+mod shake;
+mod streaming;
+mod text;
+mod tracker;
 
 use core::borrow::Borrow;
 
 use alloc::vec::Vec;
 
+use camera::Camera;
+use colour::Easing;
+use input::{Action, Bindings, Input};
+use menu::{Menu, MenuStatus};
+use palette::{PaletteEffects, PaletteTarget, Tint};
 use rng::get_random;
+use settings::{Rules, Settings};
+use sfx::SoundId;
+use shake::Shake;
+use streaming::TileStream;
+use text::TextLayer;
 
 use agb::{
     display::{
         background::{BackgroundDistributor, BackgroundRegular},
         object::{ObjectControl, ObjectStandard},
+        palette16::Palette16,
         Priority, HEIGHT, WIDTH,
     },
-    input::{Button, ButtonController, Tri},
+    input::Tri,
     number::{FixedNum, Rect, Vector2D},
 };
-use generational_arena::Arena;
+use generational_arena::{Arena, Index};
 
 agb::include_gfx!("gfx/objects.toml");
 agb::include_gfx!("gfx/background.toml");
 
 type Number = FixedNum<8>;
 
+#[derive(Clone)]
+struct BossArena {
+    // where the camera locks to while the boss is being fought
+    camera: Vector2D<Number>,
+    size: Vector2D<Number>,
+    spawn: Vector2D<Number>,
+    waypoints: Vec<Vector2D<Number>>,
+}
+
+impl BossArena {
+    fn load() -> Self {
+        let waypoints = tilemap::BOSS_WAYPOINTS_X
+            .iter()
+            .zip(tilemap::BOSS_WAYPOINTS_Y)
+            .map(|(&x, &y)| (x as i32, y as i32).into())
+            .collect();
+
+        Self {
+            camera: (tilemap::BOSS_ARENA_X as i32, tilemap::BOSS_ARENA_Y as i32).into(),
+            size: (
+                tilemap::BOSS_ARENA_WIDTH as i32,
+                tilemap::BOSS_ARENA_HEIGHT as i32,
+            )
+                .into(),
+            spawn: (tilemap::BOSS_SPAWN_X as i32, tilemap::BOSS_SPAWN_Y as i32).into(),
+            waypoints,
+        }
+    }
+}
+
+struct ScrollTrigger {
+    position: Vector2D<Number>,
+    velocity: Vector2D<Number>,
+    until_clear: bool,
+}
+
+impl ScrollTrigger {
+    fn load(i: usize) -> Self {
+        let direction: Vector2D<Number> = match tilemap::SCROLL_TRIGGER_DIRECTIONS[i] {
+            0 => (1, 0),
+            1 => (-1, 0),
+            2 => (0, 1),
+            3 => (0, -1),
+            _ => unreachable!(),
+        }
+        .into();
+
+        Self {
+            position: (
+                tilemap::SCROLL_TRIGGERS_X[i] as i32,
+                tilemap::SCROLL_TRIGGERS_Y[i] as i32,
+            )
+                .into(),
+            velocity: direction * Number::from_raw(tilemap::SCROLL_TRIGGER_SPEEDS[i] as i32),
+            until_clear: tilemap::SCROLL_TRIGGER_UNTIL_CLEAR[i],
+        }
+    }
+}
+
+struct ParallaxLayer {
+    stream: TileStream,
+    // how far the layer moves compared to the camera, 1 moves with the map and 0 stays still
+    parallax: Number,
+    // how far the layer moves each frame on its own
+    drift: Vector2D<Number>,
+    drifted: Vector2D<Number>,
+}
+
+impl ParallaxLayer {
+    fn load(mut background: BackgroundRegular<'static>, i: usize) -> Self {
+        background.set_priority(match tilemap::LAYER_PRIORITIES[i] {
+            0 => Priority::P0,
+            1 => Priority::P1,
+            2 => Priority::P2,
+            _ => Priority::P3,
+        });
+        background.commit();
+        background.show();
+
+        let stream = TileStream::new(
+            background,
+            tilemap::LAYER_MAPS[i],
+            tilemap::WIDTH,
+            tilemap::HEIGHT,
+            (0, 0).into(),
+        );
+
+        Self {
+            stream,
+            parallax: Number::from_raw(tilemap::LAYER_PARALLAX[i] as i32),
+            drift: (
+                Number::from_raw(tilemap::LAYER_DRIFT_X[i] as i32),
+                Number::from_raw(tilemap::LAYER_DRIFT_Y[i] as i32),
+            )
+                .into(),
+            drifted: (0, 0).into(),
+        }
+    }
+
+    fn commit(&mut self, offset: Vector2D<Number>) {
+        self.drifted += self.drift;
+        self.stream.update(offset * self.parallax + self.drifted);
+    }
+}
+
 struct Level {
-    background: BackgroundRegular<'static>,
-    foreground: BackgroundRegular<'static>,
-    clouds: BackgroundRegular<'static>,
+    layers: Vec<ParallaxLayer>,
 
     slime_spawns: Vec<(u16, u16)>,
+    slime_variants: Vec<SlimeVariant>,
     bat_spawns: Vec<(u16, u16)>,
+    bat_patrols: Vec<Vec<(u16, u16)>>,
     emu_spawns: Vec<(u16, u16)>,
+    spitter_spawns: Vec<(u16, u16)>,
+    scroll_triggers: Vec<ScrollTrigger>,
+    boss_arena: BossArena,
 }
 
 impl Level {
-    fn load_level(
-        mut backdrop: BackgroundRegular<'static>,
-        mut foreground: BackgroundRegular<'static>,
-        mut clouds: BackgroundRegular<'static>,
-    ) -> Self {
-        backdrop.set_position(Vector2D::new(0, 0));
-        backdrop.set_map(agb::display::background::Map::new(
-            tilemap::BACKGROUND_MAP,
-            Vector2D::new(tilemap::WIDTH, tilemap::HEIGHT),
-            0,
-        ));
-        backdrop.set_priority(Priority::P2);
-
-        foreground.set_position(Vector2D::new(0, 0));
-        foreground.set_map(agb::display::background::Map::new(
-            tilemap::FOREGROUND_MAP,
-            Vector2D::new(tilemap::WIDTH, tilemap::HEIGHT),
-            0,
-        ));
-        foreground.set_priority(Priority::P0);
-
-        clouds.set_position(Vector2D::new(0, -5));
-        clouds.set_map(agb::display::background::Map::new(
-            tilemap::CLOUD_MAP,
-            Vector2D::new(tilemap::WIDTH, tilemap::HEIGHT),
-            0,
-        ));
-        clouds.set_priority(Priority::P3);
-
-        backdrop.commit();
-        foreground.commit();
-        clouds.commit();
-
-        backdrop.show();
-        foreground.show();
-        clouds.show();
+    // takes one background for each of the layers in the map
+    fn load_level(backgrounds: Vec<BackgroundRegular<'static>>) -> Self {
+        let layers = backgrounds
+            .into_iter()
+            .enumerate()
+            .map(|(i, background)| ParallaxLayer::load(background, i))
+            .collect();
 
         let slime_spawns = tilemap::SLIME_SPAWNS_X
             .iter()
@@ -83,26 +183,55 @@ impl Level {
             .map(|(i, x)| (*x, tilemap::SLIME_SPAWNS_Y[i]))
             .collect();
 
+        let slime_variants = tilemap::SLIME_VARIANTS
+            .iter()
+            .map(|&id| SlimeVariant::from_map_id(id))
+            .collect();
+
         let bat_spawns = tilemap::BAT_SPAWNS_X
             .iter()
             .enumerate()
             .map(|(i, x)| (*x, tilemap::BAT_SPAWNS_Y[i]))
             .collect();
 
+        let bat_patrols = tilemap::BAT_PATROL_STARTS
+            .iter()
+            .zip(tilemap::BAT_PATROL_LENGTHS)
+            .map(|(&start, &length)| {
+                let waypoints = start as usize..(start + length) as usize;
+                tilemap::BAT_WAYPOINTS_X[waypoints.clone()]
+                    .iter()
+                    .zip(&tilemap::BAT_WAYPOINTS_Y[waypoints])
+                    .map(|(x, y)| (*x, *y))
+                    .collect()
+            })
+            .collect();
+
         let emu_spawns = tilemap::EMU_SPAWNS_X
             .iter()
             .enumerate()
             .map(|(i, x)| (*x, tilemap::EMU_SPAWNS_Y[i]))
             .collect();
 
+        let spitter_spawns = tilemap::SPITTER_SPAWNS_X
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (*x, tilemap::SPITTER_SPAWNS_Y[i]))
+            .collect();
+
         Self {
-            background: backdrop,
-            foreground,
-            clouds,
+            layers,
 
             slime_spawns,
+            slime_variants,
             bat_spawns,
+            bat_patrols,
             emu_spawns,
+            spitter_spawns,
+            scroll_triggers: (0..tilemap::SCROLL_TRIGGERS_X.len())
+                .map(ScrollTrigger::load)
+                .collect(),
+            boss_arena: BossArena::load(),
         }
     }
 
@@ -110,21 +239,33 @@ impl Level {
         let factor: Number = Number::new(1) / Number::new(8);
         let (x, y) = (v * factor).floor().get();
 
-        if (x < 0 || x > tilemap::WIDTH as i32) || (y < 0 || y > tilemap::HEIGHT as i32) {
+        if (x < 0 || x >= tilemap::WIDTH as i32) || (y < 0 || y >= tilemap::HEIGHT as i32) {
             return Some(Rect::new((x * 8, y * 8).into(), (8, 8).into()));
         }
         let position = tilemap::WIDTH as usize * y as usize + x as usize;
-        let tile_foreground = tilemap::FOREGROUND_MAP[position];
-        let tile_background = tilemap::BACKGROUND_MAP[position];
-        let tile_foreground_property = tilemap::TILE_TYPES[tile_foreground as usize];
-        let tile_background_property = tilemap::TILE_TYPES[tile_background as usize];
+        let collides = tilemap::COLLISION_LAYERS.iter().any(|&layer| {
+            let tile = tilemap::LAYER_MAPS[layer][position];
+            tilemap::TILE_TYPES[tile as usize] == 1
+        });
 
-        if tile_foreground_property == 1 || tile_background_property == 1 {
+        if collides {
             Some(Rect::new((x * 8, y * 8).into(), (8, 8).into()))
         } else {
             None
         }
     }
+
+    // cheap line of sight check which samples the collision map every few pixels along the line
+    fn line_of_sight(&self, from: Vector2D<Number>, to: Vector2D<Number>) -> bool {
+        let difference = to - from;
+        let steps = (difference.manhattan_distance() / 4).floor();
+        if steps == 0 {
+            return true;
+        }
+
+        let step = difference / steps;
+        (1..steps).all(|i| self.collides(from + step * i).is_none())
+    }
 }
 
 struct Entity<'a> {
@@ -391,6 +532,14 @@ impl SwordState {
         }
     }
 
+    // how many different targets a single swing can hit
+    fn pierce(self) -> usize {
+        match self {
+            SwordState::Swordless => 0,
+            _ => usize::MAX,
+        }
+    }
+
     fn cooldown_time(self) -> u16 {
         match self {
             SwordState::LongSword => 20,
@@ -507,6 +656,37 @@ enum AttackTimer {
     Cooldown(u16),
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum HitTarget {
+    Enemy(Index),
+    Boss,
+}
+
+// a single swing of the sword, remembering everything it has already hit
+struct PlayerAttack {
+    id: u16,
+    targets_hit: Vec<HitTarget>,
+    pierce: usize,
+}
+
+impl PlayerAttack {
+    fn new(id: u16, pierce: usize) -> Self {
+        Self {
+            id,
+            targets_hit: Vec::new(),
+            pierce,
+        }
+    }
+
+    fn can_hit(&self, target: HitTarget) -> bool {
+        self.targets_hit.len() < self.pierce && !self.targets_hit.contains(&target)
+    }
+
+    fn record_hit(&mut self, target: HitTarget) {
+        self.targets_hit.push(target);
+    }
+}
+
 struct Player<'a> {
     entity: Entity<'a>,
     facing: Tri,
@@ -515,13 +695,18 @@ struct Player<'a> {
     attack_timer: AttackTimer,
     damage_cooldown: u16,
     sword: SwordState,
+    // how many more hits the player can take before losing their sword
+    hits_left: u8,
+    rules: Rules,
     fudge_factor: Vector2D<i32>,
     hurtbox: Option<Rect<Number>>,
+    attack: Option<PlayerAttack>,
+    attack_count: u16,
     controllable: bool,
 }
 
 impl<'a> Player<'a> {
-    fn new(object_controller: &'a ObjectControl) -> Player {
+    fn new(object_controller: &'a ObjectControl, rules: Rules) -> Player {
         let mut entity = Entity::new(
             object_controller,
             Rect::new((0_u16, 0_u16).into(), (4_u16, 12_u16).into()),
@@ -539,31 +724,30 @@ impl<'a> Player<'a> {
             facing: Tri::Positive,
             state: PlayerState::OnGround,
             sword: SwordState::LongSword,
+            hits_left: rules.hits_per_sword,
+            rules,
             sprite_offset: 0,
             attack_timer: AttackTimer::Idle,
             fudge_factor: (0, 0).into(),
             hurtbox: None,
+            attack: None,
+            attack_count: 0,
             damage_cooldown: 0,
             controllable: true,
         }
     }
 
-    fn update(
-        &mut self,
-        buttons: &ButtonController,
-        level: &Level,
-        sfx: &mut sfx::Sfx,
-    ) -> UpdateInstruction {
+    fn update(&mut self, input: &Input, level: &Level, sfx: &mut sfx::Sfx) -> UpdateInstruction {
         let mut instruction = UpdateInstruction::None;
 
         let x = if self.controllable {
-            buttons.x_tri()
+            input.x_tri()
         } else {
             Tri::Zero
         };
 
-        let b_press = buttons.is_just_pressed(Button::B) && self.controllable;
-        let a_press = buttons.is_just_pressed(Button::A) && self.controllable;
+        let attack_press = input.is_just_pressed(Action::Attack) && self.controllable;
+        let jump_press = input.is_just_pressed(Action::Jump) && self.controllable;
 
         self.fudge_factor = (0, 0).into();
         let mut hurtbox = None;
@@ -590,15 +774,16 @@ impl<'a> Player<'a> {
                                 .set_tile_id(self.sword.idle_animation(&mut self.sprite_offset));
                         }
 
-                        if b_press && self.sword != SwordState::Swordless {
+                        if attack_press && self.sword != SwordState::Swordless {
                             self.attack_timer = AttackTimer::Attack(self.sword.attack_duration());
-                            sfx.sword();
-                        } else if a_press {
+                            self.start_attack();
+                            sfx.play(SoundId::Sword);
+                        } else if jump_press {
                             self.entity.velocity.y -= self.sword.jump_impulse();
                             self.state = PlayerState::InAir;
                             self.sprite_offset = 0;
 
-                            sfx.jump();
+                            sfx.play(SoundId::Jump);
                         }
                     }
                     AttackTimer::Attack(a) => {
@@ -654,13 +839,14 @@ impl<'a> Player<'a> {
                         self.entity.sprite.set_hflip(self.facing == Tri::Negative);
                         self.entity.velocity.x += self.sword.air_move_force() * x as i32;
 
-                        if b_press
+                        if attack_press
                             && self.sword != SwordState::LongSword
                             && self.sword != SwordState::Swordless
                         {
-                            sfx.sword();
+                            sfx.play(SoundId::Sword);
                             self.attack_timer =
                                 AttackTimer::Attack(self.sword.jump_attack_duration());
+                            self.start_attack();
                         }
                     }
                     AttackTimer::Attack(a) => {
@@ -711,7 +897,7 @@ impl<'a> Player<'a> {
                     self.entity.position + (2 * self.facing as i32, 0).into(),
                 );
 
-                sfx.player_land();
+                sfx.play(SoundId::PlayerLand);
             }
 
             self.state = PlayerState::OnGround;
@@ -728,13 +914,24 @@ impl<'a> Player<'a> {
         instruction
     }
 
+    fn start_attack(&mut self) {
+        self.attack_count = self.attack_count.wrapping_add(1);
+        self.attack = Some(PlayerAttack::new(self.attack_count, self.sword.pierce()));
+    }
+
     // retuns true if the player is alive and false otherwise
     fn damage(&mut self) -> (bool, bool) {
         if self.damage_cooldown != 0 {
             return (true, false);
         }
 
-        self.damage_cooldown = 120;
+        self.damage_cooldown = self.rules.invulnerability;
+        if self.hits_left > 1 {
+            self.hits_left -= 1;
+            return (true, true);
+        }
+
+        self.hits_left = self.rules.hits_per_sword;
         let new_sword = match self.sword {
             SwordState::LongSword => Some(SwordState::ShortSword),
             SwordState::ShortSword => Some(SwordState::Dagger),
@@ -761,6 +958,7 @@ impl<'a> Player<'a> {
             self.sword = sword;
         }
 
+        self.hits_left = self.rules.hits_per_sword;
         self.damage_cooldown = 30;
     }
 
@@ -774,29 +972,113 @@ enum EnemyData {
     Bat(BatData),
     MiniFlame(MiniFlameData),
     Emu(EmuData),
+    Spitter(SpitterData),
 }
 
 struct BatData {
     sprite_offset: u16,
     bat_state: BatState,
+    home: Vector2D<Number>,
+    patrol: Vec<Vector2D<Number>>,
+    next_waypoint: usize,
+    frames_without_sight: u16,
 }
 
 enum BatState {
-    Idle,
+    Patrolling,
     Chasing(u16),
+    Returning,
     Dead,
 }
 
 struct SlimeData {
     sprite_offset: u16,
     slime_state: SlimeState,
+    variant: SlimeVariant,
 }
 
 impl BatData {
-    fn new() -> Self {
+    fn new(home: Vector2D<Number>, patrol: Vec<Vector2D<Number>>) -> Self {
         Self {
             sprite_offset: 0,
-            bat_state: BatState::Idle,
+            bat_state: BatState::Patrolling,
+            home,
+            patrol,
+            next_waypoint: 0,
+            frames_without_sight: 0,
+        }
+    }
+
+    fn hit(&mut self, entity: &Entity, sfx: &mut sfx::Sfx) -> Option<UpdateInstruction> {
+        match self.bat_state {
+            BatState::Dead => None,
+            _ => {
+                self.bat_state = BatState::Dead;
+                sfx.play_at(SoundId::BatDeath, entity.position);
+                Some(UpdateInstruction::None)
+            }
+        }
+    }
+
+    fn flap(sprite_offset: &mut u16, entity: &mut Entity, sfx: &mut sfx::Sfx, frame_length: u16) {
+        *sprite_offset += 1;
+        if *sprite_offset >= 9 * frame_length {
+            *sprite_offset = 0;
+        }
+
+        if *sprite_offset == frame_length * 5 {
+            sfx.play_at(SoundId::BatFlap, entity.position);
+        }
+
+        entity
+            .sprite
+            .set_tile_id((78 + *sprite_offset / frame_length) * 4);
+    }
+
+    // gently accelerates towards the target, which makes the bat swoop in arcs rather than
+    // flying in straight lines, and turns away from any walls directly ahead
+    fn fly_towards(
+        entity: &mut Entity,
+        level: &Level,
+        target: Vector2D<Number>,
+        speed: Number,
+    ) -> Vector2D<Number> {
+        let difference = target - entity.position;
+        let desired_velocity = if difference.manhattan_distance() > 1.into() {
+            difference.normalise() * speed
+        } else {
+            (0, 0).into()
+        };
+
+        let desired_velocity = Self::avoid_walls(entity, level, desired_velocity);
+        entity.velocity += (desired_velocity - entity.velocity) / 16;
+        entity.update_position(level)
+    }
+
+    fn avoid_walls(entity: &Entity, level: &Level, velocity: Vector2D<Number>) -> Vector2D<Number> {
+        if velocity.manhattan_distance() < Number::new(1) / 16 {
+            return velocity;
+        }
+
+        let is_clear = |v: Vector2D<Number>| {
+            level
+                .collides(entity.position + v.normalise() * 8)
+                .is_none()
+        };
+
+        if is_clear(velocity) {
+            return velocity;
+        }
+
+        let left: Vector2D<Number> = (velocity.y, -velocity.x).into();
+        let right: Vector2D<Number> = (-velocity.y, velocity.x).into();
+
+        if is_clear(left) {
+            left
+        } else if is_clear(right) {
+            right
+        } else {
+            -velocity
         }
     }
 
@@ -808,74 +1090,81 @@ impl BatData {
         sfx: &mut sfx::Sfx,
     ) -> UpdateInstruction {
         let mut instruction = UpdateInstruction::None;
-        let should_die = player
-            .hurtbox
-            .as_ref()
-            .map(|hurtbox| hurtbox.touches(entity.collider()))
-            .unwrap_or(false);
         let should_damage = entity.collider().touches(player.entity.collider());
 
         match &mut self.bat_state {
-            BatState::Idle => {
-                self.sprite_offset += 1;
-                if self.sprite_offset >= 9 * 8 {
-                    self.sprite_offset = 0;
-                }
+            BatState::Patrolling => {
+                Self::flap(&mut self.sprite_offset, entity, sfx, 8);
 
-                if self.sprite_offset == 8 * 5 {
-                    sfx.bat_flap();
-                }
+                if let Some(&waypoint) = self.patrol.get(self.next_waypoint) {
+                    let speed = Number::new(1) / Number::new(8);
+                    Self::fly_towards(entity, level, waypoint, speed);
 
-                entity.sprite.set_tile_id((78 + self.sprite_offset / 8) * 4);
+                    if (waypoint - entity.position).manhattan_distance() < 2.into() {
+                        self.next_waypoint = (self.next_waypoint + 1) % self.patrol.len();
+                    }
+                } else {
+                    entity.velocity *= Number::new(15) / 16;
+                    entity.update_position(level);
+                }
 
-                if (entity.position - player.entity.position).manhattan_distance() < 50.into() {
+                if (entity.position - player.entity.position).manhattan_distance() < 50.into()
+                    && level.line_of_sight(entity.position, player.entity.position)
+                {
                     self.bat_state = BatState::Chasing(300);
-                    self.sprite_offset /= 4;
+                    self.frames_without_sight = 0;
+                    self.sprite_offset = 0;
                 }
 
-                if should_die {
-                    self.bat_state = BatState::Dead;
-                    sfx.bat_death();
-                } else if should_damage {
+                if should_damage {
                     instruction = UpdateInstruction::DamagePlayer;
                 }
-
-                entity.velocity *= Number::new(15) / 16;
-                entity.update_position(level);
             }
             BatState::Chasing(count) => {
-                self.sprite_offset += 1;
+                Self::flap(&mut self.sprite_offset, entity, sfx, 2);
 
-                let speed = Number::new(1) / Number::new(4);
-                let target_velocity = player.entity.position - entity.position;
-                if target_velocity.manhattan_distance() > 1.into() {
-                    entity.velocity = target_velocity.normalise() * speed;
+                // aim above the player while far away so that the bat dives down on them
+                let horizontal_distance = (player.entity.position.x - entity.position.x).abs();
+                let swoop_height = if horizontal_distance > 48.into() {
+                    Number::new(24)
                 } else {
-                    entity.velocity = (0, 0).into();
+                    horizontal_distance / 2
+                };
+                let target = player.entity.position - (0.into(), swoop_height).into();
+
+                let speed = Number::new(3) / Number::new(8);
+                Self::fly_towards(entity, level, target, speed);
+
+                if level.line_of_sight(entity.position, player.entity.position) {
+                    self.frames_without_sight = 0;
+                } else {
+                    self.frames_without_sight += 1;
                 }
 
-                if self.sprite_offset >= 9 * 2 {
+                if *count == 0 || self.frames_without_sight > 60 {
+                    self.bat_state = BatState::Returning;
                     self.sprite_offset = 0;
+                } else {
+                    *count -= 1;
                 }
-                entity.sprite.set_tile_id((78 + self.sprite_offset / 2) * 4);
 
-                if self.sprite_offset == 2 * 5 {
-                    sfx.bat_flap();
+                if should_damage {
+                    instruction = UpdateInstruction::DamagePlayer;
                 }
+            }
+            BatState::Returning => {
+                Self::flap(&mut self.sprite_offset, entity, sfx, 4);
 
-                entity.update_position(level);
+                let speed = Number::new(1) / Number::new(4);
+                Self::fly_towards(entity, level, self.home, speed);
 
-                if *count == 0 {
-                    self.bat_state = BatState::Idle;
-                    self.sprite_offset *= 4;
-                } else {
-                    *count -= 1;
+                if (self.home - entity.position).manhattan_distance() < 2.into() {
+                    self.bat_state = BatState::Patrolling;
+                    self.next_waypoint = 0;
+                    self.sprite_offset = 0;
                 }
 
-                if should_die {
-                    self.bat_state = BatState::Dead;
-                    sfx.bat_death();
-                } else if should_damage {
+                if should_damage {
                     instruction = UpdateInstruction::DamagePlayer;
                 }
             }
@@ -907,14 +1196,94 @@ impl BatData {
 enum SlimeState {
     Idle,
     Chasing(Tri),
+    // the direction towards the surface the slime is stuck to, and which way it is crawling
+    Crawling(Vector2D<Number>, Tri),
     Dead(u16),
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SlimeVariant {
+    Normal,
+    Large,
+    Small,
+    Climbing,
+}
+
+impl SlimeVariant {
+    // matches the order of the variants listed in build.rs
+    fn from_map_id(id: u8) -> Self {
+        match id {
+            0 => SlimeVariant::Normal,
+            1 => SlimeVariant::Large,
+            2 => SlimeVariant::Small,
+            3 => SlimeVariant::Climbing,
+            _ => unreachable!(),
+        }
+    }
+
+    fn collision_mask(self) -> Rect<u16> {
+        match self {
+            SlimeVariant::Normal => Rect::new((0u16, 0u16).into(), (4u16, 11u16).into()),
+            SlimeVariant::Large => Rect::new((0u16, 0u16).into(), (8u16, 13u16).into()),
+            SlimeVariant::Small => Rect::new((0u16, 0u16).into(), (4u16, 7u16).into()),
+            SlimeVariant::Climbing => Rect::new((0u16, 0u16).into(), (6u16, 6u16).into()),
+        }
+    }
+
+    fn hop_speed(self) -> Number {
+        match self {
+            SlimeVariant::Normal => Number::new(1) / 5,
+            SlimeVariant::Large => Number::new(1) / 8,
+            SlimeVariant::Small => Number::new(1) / 4,
+            SlimeVariant::Climbing => Number::new(1) / 5,
+        }
+    }
+
+    fn sight_range(self) -> Number {
+        match self {
+            SlimeVariant::Normal => 40.into(),
+            SlimeVariant::Large => 40.into(),
+            SlimeVariant::Small => 60.into(),
+            SlimeVariant::Climbing => 0.into(),
+        }
+    }
+
+    // see ENEMY_PALETTE_SWAPS
+    fn palette(self) -> u16 {
+        match self {
+            SlimeVariant::Normal => 0,
+            SlimeVariant::Large => 2,
+            SlimeVariant::Small => 3,
+            SlimeVariant::Climbing => 4,
+        }
+    }
+
+    // what this slime breaks up into when it dies
+    fn splits_into(self) -> Option<(SlimeVariant, u16)> {
+        match self {
+            SlimeVariant::Large => Some((SlimeVariant::Small, 2)),
+            _ => None,
+        }
+    }
+}
+
 impl SlimeData {
-    fn new() -> Self {
+    fn new(variant: SlimeVariant) -> Self {
         Self {
             sprite_offset: 0,
             slime_state: SlimeState::Idle,
+            variant,
+        }
+    }
+
+    fn hit(&mut self, entity: &Entity, sfx: &mut sfx::Sfx) -> Option<UpdateInstruction> {
+        match self.slime_state {
+            SlimeState::Dead(_) => None,
+            _ => {
+                self.slime_state = SlimeState::Dead(0);
+                sfx.play_at(SoundId::SlimeDeath, entity.position);
+                Some(UpdateInstruction::None)
+            }
         }
     }
 
@@ -927,11 +1296,6 @@ impl SlimeData {
     ) -> UpdateInstruction {
         let mut instruction = UpdateInstruction::None;
 
-        let should_die = player
-            .hurtbox
-            .as_ref()
-            .map(|h| h.touches(entity.collider()))
-            .unwrap_or(false);
         let should_damage = entity.collider().touches(player.entity.collider());
 
         match &mut self.slime_state {
@@ -945,7 +1309,16 @@ impl SlimeData {
                     .sprite
                     .set_tile_id((29 + self.sprite_offset / 16) * 4);
 
-                if (player.entity.position - entity.position).manhattan_distance() < 40.into() {
+                if self.variant == SlimeVariant::Climbing {
+                    let (_, on_ground) =
+                        entity
+                            .collision_in_direction((0, 1).into(), 1.into(), |v| level.collides(v));
+                    if on_ground {
+                        self.slime_state = SlimeState::Crawling((0, 1).into(), Tri::Positive);
+                    }
+                } else if (player.entity.position - entity.position).manhattan_distance()
+                    < self.variant.sight_range()
+                {
                     let direction = if player.entity.position.x > entity.position.x {
                         Tri::Positive
                     } else if player.entity.position.x < entity.position.x {
@@ -957,9 +1330,7 @@ impl SlimeData {
                     self.slime_state = SlimeState::Chasing(direction);
                     self.sprite_offset = 0;
                 }
-                if should_die {
-                    self.slime_state = SlimeState::Dead(0);
-                } else if should_damage {
+                if should_damage {
                     instruction = UpdateInstruction::DamagePlayer
                 }
 
@@ -977,13 +1348,13 @@ impl SlimeData {
                     let frame = ping_pong(self.sprite_offset / 6, 5);
 
                     if frame == 0 {
-                        sfx.slime_boing();
+                        sfx.play_at(SoundId::SlimeBoing, entity.position);
                     }
 
                     entity.sprite.set_tile_id((frame + 31) * 4);
 
                     entity.velocity.x = match frame {
-                        2 | 3 | 4 => (Number::new(1) / 5) * Number::new(*direction as i32),
+                        2 | 3 | 4 => self.variant.hop_speed() * Number::new(*direction as i32),
                         _ => 0.into(),
                     };
 
@@ -997,15 +1368,41 @@ impl SlimeData {
                         self.sprite_offset = 6 * 6;
                     }
                 }
-                if should_die {
-                    self.slime_state = SlimeState::Dead(0);
-                    sfx.slime_dead();
-                } else if should_damage {
+                if should_damage {
+                    instruction = UpdateInstruction::DamagePlayer
+                }
+            }
+            SlimeState::Crawling(surface, direction) => {
+                self.sprite_offset += 1;
+                if self.sprite_offset >= 32 {
+                    self.sprite_offset = 0;
+                }
+
+                entity
+                    .sprite
+                    .set_tile_id((29 + self.sprite_offset / 16) * 4);
+                entity.sprite.set_vflip(surface.y < 0.into());
+
+                Self::crawl(entity, level, surface, *direction, self.variant.hop_speed());
+
+                if should_damage {
                     instruction = UpdateInstruction::DamagePlayer
                 }
             }
             SlimeState::Dead(count) => {
                 if *count < 5 * 4 {
+                    if let Some((variant, pieces)) = self.variant.splits_into() {
+                        // spawn one piece per frame, alternating which way they are flung
+                        if *count < pieces {
+                            let direction = if *count % 2 == 0 { 1 } else { -1 };
+                            instruction = UpdateInstruction::CreateEnemy(
+                                EnemyData::Slime(SlimeData::new(variant)),
+                                entity.position,
+                                (direction, -1).into(),
+                            );
+                        }
+                    }
+
                     entity.sprite.set_tile_id((36 + *count / 4) * 4);
                     *count += 1;
                 } else {
@@ -1015,6 +1412,40 @@ impl SlimeData {
         }
         instruction
     }
+
+    // Moves along whatever surface the slime is stuck to, following it around corners.
+    fn crawl(
+        entity: &mut Entity,
+        level: &Level,
+        surface: &mut Vector2D<Number>,
+        direction: Tri,
+        speed: Number,
+    ) {
+        const CRAWL_HEIGHT: i32 = 5;
+
+        let forward: Vector2D<Number> =
+            (surface.y * direction as i32, -surface.x * direction as i32).into();
+
+        if level
+            .collides(entity.position + forward * (CRAWL_HEIGHT + 1))
+            .is_some()
+        {
+            // inside corner, start climbing the wall in front of us
+            *surface = forward;
+            return;
+        }
+
+        entity.position += forward * speed;
+
+        if level
+            .collides(entity.position + *surface * (CRAWL_HEIGHT + 1))
+            .is_none()
+        {
+            // outside corner, wrap around the edge we've just walked off
+            entity.position += forward * CRAWL_HEIGHT + *surface * (2 * CRAWL_HEIGHT);
+            *surface = -forward;
+        }
+    }
 }
 
 enum MiniFlameState {
@@ -1026,13 +1457,35 @@ enum MiniFlameState {
 struct MiniFlameData {
     state: MiniFlameState,
     sprite_offset: u16,
+    // drops health one time in this many when defeated
+    heal_drop_chance: i32,
 }
 
 impl MiniFlameData {
-    fn new() -> Self {
+    fn new(heal_drop_chance: i32) -> Self {
         Self {
             state: MiniFlameState::Chasing(90),
             sprite_offset: 0,
+            heal_drop_chance,
+        }
+    }
+
+    fn hit(&mut self, entity: &Entity) -> Option<UpdateInstruction> {
+        match self.state {
+            MiniFlameState::Dead => None,
+            _ => {
+                self.sprite_offset = 0;
+                self.state = MiniFlameState::Dead;
+
+                if get_random() % self.heal_drop_chance == 0 {
+                    Some(UpdateInstruction::CreateParticle(
+                        ParticleData::new_health(),
+                        entity.position,
+                    ))
+                } else {
+                    Some(UpdateInstruction::None)
+                }
+            }
         }
     }
 
@@ -1045,11 +1498,6 @@ impl MiniFlameData {
     ) -> UpdateInstruction {
         let mut instruction = UpdateInstruction::None;
 
-        let should_die = player
-            .hurtbox
-            .as_ref()
-            .map(|h| h.touches(entity.collider()))
-            .unwrap_or(false);
         let should_damage = entity.collider().touches(player.entity.collider());
 
         self.sprite_offset += 1;
@@ -1063,7 +1511,7 @@ impl MiniFlameData {
                     if resulting_direction.manhattan_distance() < 1.into() {
                         self.state = MiniFlameState::Idle(30);
                     } else {
-                        sfx.flame_charge();
+                        sfx.play_at(SoundId::FlameCharge, entity.position);
                         self.state = MiniFlameState::Chasing(90);
                         entity.velocity = resulting_direction.normalise() * Number::new(2);
                     }
@@ -1079,17 +1527,7 @@ impl MiniFlameData {
                     entity.velocity = (0.into(), Number::new(-1) / Number::new(4)).into();
                 }
 
-                if should_die {
-                    self.sprite_offset = 0;
-                    self.state = MiniFlameState::Dead;
-
-                    if get_random() % 4 == 0 {
-                        instruction = UpdateInstruction::CreateParticle(
-                            ParticleData::new_health(),
-                            entity.position,
-                        );
-                    }
-                } else if should_damage {
+                if should_damage {
                     instruction = UpdateInstruction::DamagePlayer;
                 }
             }
@@ -1102,17 +1540,7 @@ impl MiniFlameData {
                     *frame -= 1;
                 }
 
-                if should_die {
-                    self.sprite_offset = 0;
-                    self.state = MiniFlameState::Dead;
-
-                    if get_random() % 4 == 0 {
-                        instruction = UpdateInstruction::CreateParticle(
-                            ParticleData::new_health(),
-                            entity.position,
-                        );
-                    }
-                } else if should_damage {
+                if should_damage {
                     instruction = UpdateInstruction::DamagePlayer;
                 }
 
@@ -1150,14 +1578,25 @@ impl MiniFlameData {
 
 enum EmuState {
     Idle,
+    WindUp(Tri, u16),
     Charging(Tri),
+    Skidding,
     Knockback,
+    Stunned(u16),
     Dead,
 }
 
+enum Ledge {
+    None,
+    Gap,
+    Drop,
+}
+
 struct EmuData {
     state: EmuState,
     sprite_offset: u16,
+    facing: Tri,
+    health: u8,
 }
 
 impl EmuData {
@@ -1165,6 +1604,67 @@ impl EmuData {
         Self {
             state: EmuState::Idle,
             sprite_offset: 0,
+            facing: Tri::Negative,
+            health: 2,
+        }
+    }
+
+    fn hit(&mut self, entity: &mut Entity, sfx: &mut sfx::Sfx) -> Option<UpdateInstruction> {
+        let damage = match self.state {
+            EmuState::Dead => return None,
+            EmuState::Stunned(_) => 2,
+            _ => 1,
+        };
+
+        self.health = self.health.saturating_sub(damage);
+        self.sprite_offset = 0;
+
+        if self.health == 0 {
+            self.state = EmuState::Dead;
+        } else {
+            sfx.play_at(SoundId::EmuCrash, entity.position);
+            self.state = EmuState::Knockback;
+            entity.velocity = (-Number::new(self.facing as i32) / 2, Number::new(-1)).into();
+        }
+
+        Some(UpdateInstruction::None)
+    }
+
+    fn start_wind_up(&mut self, entity: &mut Entity, direction: Tri, sfx: &mut sfx::Sfx) {
+        self.facing = direction;
+        self.sprite_offset = 0;
+        self.state = EmuState::WindUp(direction, 30);
+
+        entity.velocity.x = 0.into();
+        entity.sprite.set_hflip(direction == Tri::Positive);
+        sfx.play_at(SoundId::EmuWindUp, entity.position);
+    }
+
+    // Looks at the ground just in front of the emu's feet to decide whether it can keep running
+    fn ledge_ahead(entity: &Entity, level: &Level, direction: Tri) -> Ledge {
+        let collider = entity.collider();
+        let foot = entity.position
+            + (
+                (collider.size.x / 2 + 4) * direction as i32,
+                collider.size.y / 2 + 2,
+            )
+                .into();
+
+        // small steps down are fine to run off
+        if level.collides(foot).is_some() || level.collides(foot + (0, 8).into()).is_some() {
+            return Ledge::None;
+        }
+
+        let gap_has_other_side = (1..=3).any(|tiles| {
+            level
+                .collides(foot + (tiles * 8 * direction as i32, 0).into())
+                .is_some()
+        });
+
+        if gap_has_other_side {
+            Ledge::Gap
+        } else {
+            Ledge::Drop
         }
     }
 
@@ -1177,11 +1677,6 @@ impl EmuData {
     ) -> UpdateInstruction {
         let mut instruction = UpdateInstruction::None;
 
-        let should_die = player
-            .hurtbox
-            .as_ref()
-            .map(|h| h.touches(entity.collider()))
-            .unwrap_or(false);
         let should_damage = entity.collider().touches(player.entity.collider());
 
         match &mut self.state {
@@ -1197,32 +1692,50 @@ impl EmuData {
                     .set_tile_id((170 + self.sprite_offset / 16) * 4);
 
                 if (entity.position.y - player.entity.position.y).abs() < 10.into() {
-                    let velocity = Number::new(1)
-                        * (player.entity.position.x - entity.position.x)
-                            .to_raw()
-                            .signum();
-                    entity.velocity.x = velocity;
-
-                    if velocity > 0.into() {
-                        entity.sprite.set_hflip(true);
-                        self.state = EmuState::Charging(Tri::Positive);
-                    } else if velocity < 0.into() {
-                        self.state = EmuState::Charging(Tri::Negative);
-                        entity.sprite.set_hflip(false);
+                    let direction = if player.entity.position.x > entity.position.x {
+                        Tri::Positive
+                    } else if player.entity.position.x < entity.position.x {
+                        Tri::Negative
                     } else {
-                        self.state = EmuState::Idle;
+                        Tri::Zero
+                    };
+
+                    // an emu which has stopped at the edge of a drop stays there rather than
+                    // winding up to charge off it again
+                    if direction != Tri::Zero
+                        && !matches!(Self::ledge_ahead(entity, level, direction), Ledge::Drop)
+                    {
+                        self.start_wind_up(entity, direction, sfx);
                     }
                 }
 
-                if should_die {
+                if should_damage {
+                    instruction = UpdateInstruction::DamagePlayer;
+                }
+            }
+            EmuState::WindUp(direction, frames) => {
+                // paw at the ground before charging so the player has a chance to react
+                self.sprite_offset += 1;
+                if self.sprite_offset >= 2 * 8 {
                     self.sprite_offset = 0;
-                    self.state = EmuState::Dead;
-                } else if should_damage {
+                }
+
+                entity
+                    .sprite
+                    .set_tile_id((185 + self.sprite_offset / 8) * 4);
+
+                *frames -= 1;
+                if *frames == 0 {
+                    entity.velocity.x = Number::new(*direction as i32);
+                    self.state = EmuState::Charging(*direction);
+                }
+
+                if should_damage {
                     instruction = UpdateInstruction::DamagePlayer;
                 }
             }
             EmuState::Charging(direction) => {
-                let direction = Number::new(*direction as i32);
+                let direction = *direction;
                 self.sprite_offset += 1;
 
                 if self.sprite_offset >= 4 * 2 {
@@ -1230,7 +1743,7 @@ impl EmuData {
                 }
 
                 if self.sprite_offset == 2 * 2 {
-                    sfx.emu_step();
+                    sfx.play_at(SoundId::EmuStep, entity.position);
                 }
 
                 entity
@@ -1241,18 +1754,69 @@ impl EmuData {
                 let gravity = gravity / 16;
                 entity.velocity.y += gravity;
 
+                let (_, on_ground) =
+                    entity.collision_in_direction((0, 1).into(), 1.into(), |v| level.collides(v));
+
+                if on_ground {
+                    match Self::ledge_ahead(entity, level, direction) {
+                        Ledge::None => {}
+                        Ledge::Gap => {
+                            entity.velocity.y = Number::new(-5) / 4;
+                        }
+                        Ledge::Drop => {
+                            entity.velocity.x = 0.into();
+                            self.state = EmuState::Idle;
+                        }
+                    }
+                }
+
                 let distance_travelled = entity.update_position(level);
 
-                if distance_travelled.x == 0.into() {
-                    sfx.emu_crash();
-                    self.state = EmuState::Knockback;
-                    entity.velocity = (-direction / 2, Number::new(-1)).into();
+                if let EmuState::Charging(_) = self.state {
+                    let behind_by =
+                        (entity.position.x - player.entity.position.x) * direction as i32;
+
+                    if distance_travelled.x == 0.into() {
+                        sfx.play_at(SoundId::EmuCrash, entity.position);
+                        self.state = EmuState::Knockback;
+                        entity.velocity =
+                            (-Number::new(direction as i32) / 2, Number::new(-1)).into();
+                        instruction = UpdateInstruction::Crash(direction);
+                    } else if on_ground && behind_by > 24.into() {
+                        self.state = EmuState::Skidding;
+                    }
                 }
 
-                if should_die {
-                    self.sprite_offset = 0;
-                    self.state = EmuState::Dead;
-                } else if should_damage {
+                if should_damage {
+                    // crashing into the player still shakes the screen as well as hurting them
+                    instruction = match instruction {
+                        UpdateInstruction::Crash(direction) => {
+                            UpdateInstruction::DamagePlayerAndCrash(direction)
+                        }
+                        _ => UpdateInstruction::DamagePlayer,
+                    };
+                }
+            }
+            EmuState::Skidding => {
+                entity.sprite.set_tile_id(170 * 4);
+
+                let gravity: Number = 1.into();
+                let gravity = gravity / 16;
+                entity.velocity.y += gravity;
+                entity.velocity.x = entity.velocity.x * 7 / 8;
+
+                entity.update_position(level);
+
+                if entity.velocity.x.abs() < Number::new(1) / 8 {
+                    let turn_to = if self.facing == Tri::Positive {
+                        Tri::Negative
+                    } else {
+                        Tri::Positive
+                    };
+                    self.start_wind_up(entity, turn_to, sfx);
+                }
+
+                if should_damage {
                     instruction = UpdateInstruction::DamagePlayer;
                 }
             }
@@ -1267,19 +1831,26 @@ impl EmuData {
 
                 if is_collision {
                     entity.velocity.x = 0.into();
-                    self.state = EmuState::Idle;
+                    self.state = EmuState::Stunned(90);
                 }
 
-                if should_die {
-                    self.sprite_offset = 0;
-                    self.state = EmuState::Dead;
-                } else if should_damage {
+                if should_damage {
                     instruction = UpdateInstruction::DamagePlayer;
                 }
             }
+            EmuState::Stunned(frames) => {
+                // dazed after crashing, so the emu is harmless and takes extra damage
+                entity.sprite.set_tile_id((187 + (*frames / 16) % 2) * 4);
+
+                *frames -= 1;
+                if *frames == 0 {
+                    self.sprite_offset = 0;
+                    self.state = EmuState::Idle;
+                }
+            }
             EmuState::Dead => {
                 if self.sprite_offset == 0 {
-                    sfx.emu_death();
+                    sfx.play_at(SoundId::EmuDeath, entity.position);
                 }
 
                 if self.sprite_offset >= 8 * 4 {
@@ -1297,22 +1868,153 @@ impl EmuData {
     }
 }
 
+enum SpitterState {
+    Idle,
+    Aiming(u16),
+    Cooldown(u16),
+    Dead(u16),
+}
+
+struct SpitterData {
+    state: SpitterState,
+    sprite_offset: u16,
+}
+
+impl SpitterData {
+    fn new() -> Self {
+        Self {
+            state: SpitterState::Idle,
+            sprite_offset: 0,
+        }
+    }
+
+    fn hit(&mut self, entity: &Entity, sfx: &mut sfx::Sfx) -> Option<UpdateInstruction> {
+        match self.state {
+            SpitterState::Dead(_) => None,
+            _ => {
+                self.state = SpitterState::Dead(0);
+                sfx.play_at(SoundId::SlimeDeath, entity.position);
+                Some(UpdateInstruction::None)
+            }
+        }
+    }
+
+    fn update(
+        &mut self,
+        entity: &mut Entity,
+        player: &Player,
+        level: &Level,
+        sfx: &mut sfx::Sfx,
+    ) -> UpdateInstruction {
+        let mut instruction = UpdateInstruction::None;
+
+        let should_damage = entity.collider().touches(player.entity.collider());
+        let to_player = player.entity.position - entity.position;
+
+        entity
+            .sprite
+            .set_hflip(player.entity.position.x < entity.position.x);
+
+        match &mut self.state {
+            SpitterState::Idle => {
+                self.sprite_offset += 1;
+                if self.sprite_offset >= 32 {
+                    self.sprite_offset = 0;
+                }
+
+                entity
+                    .sprite
+                    .set_tile_id((29 + self.sprite_offset / 16) * 4);
+
+                if to_player.x.abs() < 120.into()
+                    && level.line_of_sight(entity.position, player.entity.position)
+                {
+                    self.sprite_offset = 0;
+                    self.state = SpitterState::Aiming(30);
+                }
+            }
+            SpitterState::Aiming(frames) => {
+                self.sprite_offset += 1;
+                let frame = ping_pong(self.sprite_offset / 6, 5);
+                entity.sprite.set_tile_id((frame + 31) * 4);
+
+                *frames -= 1;
+                if *frames == 0 {
+                    if to_player.manhattan_distance() > 1.into() {
+                        sfx.play_at(SoundId::FlameCharge, entity.position);
+                        instruction = UpdateInstruction::CreateProjectile(
+                            ProjectileData::new_flame(180),
+                            entity.position,
+                            to_player.normalise() * Number::new(3) / 2,
+                        );
+                    }
+
+                    self.state = SpitterState::Cooldown(90);
+                }
+            }
+            SpitterState::Cooldown(frames) => {
+                entity.sprite.set_tile_id(29 * 4);
+
+                *frames -= 1;
+                if *frames == 0 {
+                    self.state = SpitterState::Idle;
+                }
+            }
+            SpitterState::Dead(count) => {
+                if *count < 5 * 4 {
+                    entity.sprite.set_tile_id((36 + *count / 4) * 4);
+                    *count += 1;
+                } else {
+                    return UpdateInstruction::Remove;
+                }
+            }
+        }
+
+        if !matches!(self.state, SpitterState::Dead(_)) {
+            if should_damage {
+                instruction = UpdateInstruction::DamagePlayer;
+            }
+
+            let gravity: Number = 1.into();
+            let gravity = gravity / 16;
+            entity.velocity.y += gravity;
+            entity.velocity.x = 0.into();
+            entity.update_position(level);
+        }
+
+        instruction
+    }
+}
+
 enum UpdateInstruction {
     None,
     HealBossAndRemove,
     HealPlayerAndRemove,
     Remove,
     DamagePlayer,
+    DamagePlayerAndCrash(Tri),
     CreateParticle(ParticleData, Vector2D<Number>),
+    CreateEnemy(EnemyData, Vector2D<Number>, Vector2D<Number>),
+    CreateProjectile(ProjectileData, Vector2D<Number>, Vector2D<Number>),
+    Crash(Tri),
 }
 
 impl EnemyData {
     fn collision_mask(&self) -> Rect<u16> {
         match self {
-            EnemyData::Slime(_) => Rect::new((0u16, 0u16).into(), (4u16, 11u16).into()),
+            EnemyData::Slime(data) => data.variant.collision_mask(),
             EnemyData::Bat(_) => Rect::new((0u16, 0u16).into(), (12u16, 4u16).into()),
             EnemyData::MiniFlame(_) => Rect::new((0u16, 0u16).into(), (12u16, 12u16).into()),
             EnemyData::Emu(_) => Rect::new((0u16, 0u16).into(), (7u16, 11u16).into()),
+            EnemyData::Spitter(_) => Rect::new((0u16, 0u16).into(), (6u16, 9u16).into()),
+        }
+    }
+
+    fn palette(&self) -> u16 {
+        match self {
+            EnemyData::Slime(data) => data.variant.palette(),
+            EnemyData::Spitter(_) => 5,
+            _ => 0,
         }
     }
 
@@ -1322,6 +2024,7 @@ impl EnemyData {
             EnemyData::Bat(_) => 78,
             EnemyData::MiniFlame(_) => 137,
             EnemyData::Emu(_) => 170,
+            EnemyData::Spitter(_) => 29,
         }
     }
 
@@ -1337,6 +2040,18 @@ impl EnemyData {
             EnemyData::Bat(data) => data.update(entity, player, level, sfx),
             EnemyData::MiniFlame(data) => data.update(entity, player, level, sfx),
             EnemyData::Emu(data) => data.update(entity, player, level, sfx),
+            EnemyData::Spitter(data) => data.update(entity, player, level, sfx),
+        }
+    }
+
+    // returns None if the enemy can't currently be hit (e.g. it is already dying)
+    fn hit(&mut self, entity: &mut Entity, sfx: &mut sfx::Sfx) -> Option<UpdateInstruction> {
+        match self {
+            EnemyData::Slime(data) => data.hit(entity, sfx),
+            EnemyData::Bat(data) => data.hit(entity, sfx),
+            EnemyData::MiniFlame(data) => data.hit(entity),
+            EnemyData::Emu(data) => data.hit(entity, sfx),
+            EnemyData::Spitter(data) => data.hit(entity, sfx),
         }
     }
 }
@@ -1354,6 +2069,7 @@ impl<'a> Enemy<'a> {
             .sprite
             .set_sprite_size(agb::display::object::Size::S16x16);
         entity.sprite.set_tile_id(enemy_data.tile_id());
+        entity.sprite.set_palette(enemy_data.palette());
         entity.sprite.show();
 
         entity.sprite.commit();
@@ -1364,6 +2080,10 @@ impl<'a> Enemy<'a> {
     fn update(&mut self, player: &Player, level: &Level, sfx: &mut sfx::Sfx) -> UpdateInstruction {
         self.enemy_data.update(&mut self.entity, player, level, sfx)
     }
+
+    fn hit(&mut self, sfx: &mut sfx::Sfx) -> Option<UpdateInstruction> {
+        self.enemy_data.hit(&mut self.entity, sfx)
+    }
 }
 
 enum ParticleData {
@@ -1497,6 +2217,108 @@ impl<'a> Particle<'a> {
     }
 }
 
+struct ProjectileData {
+    lifetime: u16,
+    frame: u16,
+    reflectable: bool,
+    reflected: bool,
+}
+
+impl ProjectileData {
+    fn new_flame(lifetime: u16) -> Self {
+        Self {
+            lifetime,
+            frame: 0,
+            reflectable: true,
+            reflected: false,
+        }
+    }
+
+    fn new_flame_rain(lifetime: u16) -> Self {
+        Self {
+            lifetime,
+            frame: 0,
+            reflectable: false,
+            reflected: false,
+        }
+    }
+
+    fn tile_id(&self) -> u16 {
+        137
+    }
+
+    fn update(&mut self, entity: &mut Entity, player: &Player, level: &Level) -> UpdateInstruction {
+        if self.lifetime == 0 {
+            return UpdateInstruction::Remove;
+        }
+        self.lifetime -= 1;
+
+        entity
+            .sprite
+            .set_tile_id((self.tile_id() + (self.frame / 4) % 12) * 4);
+        self.frame += 1;
+
+        if self.reflectable && !self.reflected {
+            if let Some(hurtbox) = &player.hurtbox {
+                if hurtbox.touches(entity.collider()) {
+                    self.reflected = true;
+                    entity.velocity = -entity.velocity * 3 / 2;
+                }
+            }
+        }
+
+        entity.update_position_without_collision();
+
+        if level.collides(entity.position).is_some() {
+            return UpdateInstruction::Remove;
+        }
+
+        if !self.reflected && entity.collider().touches(player.entity.collider()) {
+            // gets removed at the start of the next frame
+            self.lifetime = 0;
+            return UpdateInstruction::DamagePlayer;
+        }
+
+        UpdateInstruction::None
+    }
+}
+
+struct Projectile<'a> {
+    entity: Entity<'a>,
+    projectile_data: ProjectileData,
+}
+
+impl<'a> Projectile<'a> {
+    fn new(
+        object_controller: &'a ObjectControl,
+        projectile_data: ProjectileData,
+        position: Vector2D<Number>,
+        velocity: Vector2D<Number>,
+    ) -> Self {
+        let mut entity = Entity::new(
+            object_controller,
+            Rect::new((0u16, 0u16).into(), (6u16, 6u16).into()),
+        );
+
+        entity
+            .sprite
+            .set_sprite_size(agb::display::object::Size::S16x16);
+        entity.sprite.set_tile_id(projectile_data.tile_id() * 4);
+        entity.sprite.show();
+        entity.position = position;
+        entity.velocity = velocity;
+
+        Self {
+            entity,
+            projectile_data,
+        }
+    }
+
+    fn update(&mut self, player: &Player, level: &Level) -> UpdateInstruction {
+        self.projectile_data.update(&mut self.entity, player, level)
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Copy)]
 enum GameStatus {
     Continue,
@@ -1515,12 +2337,21 @@ impl<'a> BossState<'a> {
     fn update(
         &mut self,
         enemies: &mut Arena<Enemy<'a>>,
+        projectiles: &mut Arena<Projectile<'a>>,
         object_controller: &'a ObjectControl,
         player: &Player,
         sfx: &mut sfx::Sfx,
+        palettes: &mut PaletteEffects,
     ) -> BossInstruction {
         match self {
-            BossState::Active(boss) => boss.update(enemies, object_controller, player, sfx),
+            BossState::Active(boss) => boss.update(
+                enemies,
+                projectiles,
+                object_controller,
+                player,
+                sfx,
+                palettes,
+            ),
             BossState::Following(boss) => {
                 boss.update(player);
                 BossInstruction::None
@@ -1528,10 +2359,10 @@ impl<'a> BossState<'a> {
             BossState::NotSpawned => BossInstruction::None,
         }
     }
-    fn commit(&mut self, offset: Vector2D<Number>) {
+    fn commit(&mut self, offset: Vector2D<Number>, shake: &Shake) {
         match self {
             BossState::Active(boss) => {
-                boss.commit(offset);
+                boss.commit(offset, shake);
             }
             BossState::Following(boss) => {
                 boss.commit(offset);
@@ -1612,27 +2443,110 @@ enum BossActiveState {
     Damaged(u8),
     MovingToTarget,
     WaitingUntilExplosion(u8),
+    // which step of the current attack pattern we're on, and how long we've waited for it
+    Attacking(usize, u16),
+    Dashing(usize, Vector2D<Number>),
     WaitingUntilDamaged(u16),
+    // the stage of the death animation and how long is left of it
+    Dying(u8, u16),
     WaitUntilKilled,
 }
 
+#[derive(Clone, Copy)]
+enum BossAttack {
+    // a handful of flames thrown out in random directions, more as the boss gets weaker
+    Burst,
+    // flames fired evenly in every direction
+    Ring(u8),
+    // flames falling from the top of the screen down to the floor
+    FlameRain(u8),
+    // a fast sweep across the screen at the player's height
+    Dash,
+}
+
+struct AttackStep {
+    delay: u16,
+    attack: BossAttack,
+}
+
+// the patterns are constants, so a ring which can't be made from RING_DIRECTIONS fails to compile
+const fn step(delay: u16, attack: BossAttack) -> AttackStep {
+    if let BossAttack::Ring(count) = attack {
+        // the flames are spaced evenly around RING_DIRECTIONS, so the count has to divide it
+        assert!(
+            count >= 1 && RING_DIRECTIONS.len() % count as usize == 0,
+            "rings can have 1, 2, 4, 8 or 16 flames"
+        );
+    }
+
+    AttackStep { delay, attack }
+}
+
+// the patterns get more elaborate as the boss loses health
+const BOSS_PATTERNS: &[&[AttackStep]] = &[
+    // health 1
+    &[
+        step(0, BossAttack::FlameRain(8)),
+        step(30, BossAttack::Dash),
+        step(10, BossAttack::Ring(16)),
+        step(40, BossAttack::Burst),
+    ],
+    // health 2
+    &[
+        step(0, BossAttack::Dash),
+        step(20, BossAttack::Ring(8)),
+        step(40, BossAttack::FlameRain(6)),
+    ],
+    // health 3
+    &[step(0, BossAttack::Burst), step(40, BossAttack::Ring(8))],
+    // health 4
+    &[step(0, BossAttack::Burst)],
+    // health 5
+    &[step(0, BossAttack::Burst)],
+];
+
+// unit vectors for 16 evenly spaced directions, as raw values of Number
+const RING_DIRECTIONS: [(i32, i32); 16] = [
+    (256, 0),
+    (237, 98),
+    (181, 181),
+    (98, 237),
+    (0, 256),
+    (-98, 237),
+    (-181, 181),
+    (-237, 98),
+    (-256, 0),
+    (-237, -98),
+    (-181, -181),
+    (-98, -237),
+    (0, -256),
+    (98, -237),
+    (181, -181),
+    (237, -98),
+];
+
 struct Boss<'a> {
     entity: Entity<'a>,
     health: u8,
-    target_location: u8,
+    target_location: usize,
     state: BossActiveState,
     timer: u32,
-    screen_coords: Vector2D<Number>,
+    arena: BossArena,
     shake_magnitude: Number,
+    last_hit_by: Option<u16>,
+    invulnerable_time: u16,
+    rules: Rules,
 }
 
 enum BossInstruction {
     None,
     Dead,
+    DamagePlayer,
+    Explode,
 }
 
 impl<'a> Boss<'a> {
-    fn new(object_controller: &'a ObjectControl, screen_coords: Vector2D<Number>) -> Self {
+    fn new(object_controller: &'a ObjectControl, arena: BossArena, rules: Rules) -> Self {
         let mut entity = Entity::new(
             object_controller,
             Rect::new((0_u16, 0_u16).into(), (28_u16, 28_u16).into()),
@@ -1641,36 +2555,55 @@ impl<'a> Boss<'a> {
             .sprite
             .set_sprite_size(agb::display::object::Size::S32x32);
         entity.sprite.set_palette(1);
-        entity.position = screen_coords + (144, 136).into();
+        entity.position = arena.spawn;
         Self {
             entity,
             health: 5,
-            target_location: get_random().rem_euclid(5) as u8,
+            target_location: get_random().rem_euclid(arena.waypoints.len() as i32) as usize,
             state: BossActiveState::Damaged(60),
             timer: 0,
-            screen_coords,
+            arena,
             shake_magnitude: 0.into(),
+            last_hit_by: None,
+            invulnerable_time: 0,
+            rules,
         }
     }
     fn update(
         &mut self,
         enemies: &mut Arena<Enemy<'a>>,
+        projectiles: &mut Arena<Projectile<'a>>,
         object_controller: &'a ObjectControl,
         player: &Player,
         sfx: &mut sfx::Sfx,
+        palettes: &mut PaletteEffects,
     ) -> BossInstruction {
         let mut instruction = BossInstruction::None;
+
+        if self.invulnerable_time > 0 {
+            self.invulnerable_time -= 1;
+        }
+
         match &mut self.state {
             BossActiveState::Damaged(time) => {
                 *time -= 1;
                 if *time == 0 {
-                    self.target_location = self.get_next_target_location();
-                    self.state = BossActiveState::MovingToTarget;
-                    sfx.boss_move();
+                    if self.health == 0 {
+                        enemies.clear();
+                        projectiles.clear();
+                        sfx.play_at(SoundId::Burning, self.entity.position);
+                        self.state = BossActiveState::Dying(0, 90);
+                        palettes.flash(PaletteTarget::Sprite(1), 0x7FFF, 8, 90);
+                        instruction = BossInstruction::Explode;
+                    } else {
+                        self.target_location = self.get_next_target_location();
+                        self.state = BossActiveState::MovingToTarget;
+                        sfx.play_at(SoundId::BossMove, self.entity.position);
+                    }
                 }
             }
             BossActiveState::MovingToTarget => {
-                let target = self.get_target_location() + self.screen_coords;
+                let target = self.arena.waypoints[self.target_location];
                 let difference = target - self.entity.position;
                 if difference.manhattan_distance() < 1.into() {
                     self.entity.velocity = (0, 0).into();
@@ -1682,29 +2615,91 @@ impl<'a> Boss<'a> {
             BossActiveState::WaitingUntilExplosion(time) => {
                 *time -= 1;
                 if *time == 0 {
-                    if self.health == 0 {
-                        enemies.clear();
-                        instruction = BossInstruction::Dead;
-                        self.state = BossActiveState::WaitUntilKilled;
-                    } else {
-                        sfx.burning();
-                        self.explode(enemies, object_controller);
-                        self.state = BossActiveState::WaitingUntilDamaged(60 * 5);
+                    self.state = BossActiveState::Attacking(0, 0);
+                }
+            }
+            BossActiveState::Attacking(step, time) => {
+                let pattern = Self::pattern(self.health);
+                if *step >= pattern.len() {
+                    self.state = BossActiveState::WaitingUntilDamaged(self.rules.boss_patience);
+                } else if *time < pattern[*step].delay {
+                    *time += 1;
+                } else {
+                    let step = *step;
+                    self.state = BossActiveState::Attacking(step + 1, 0);
+
+                    match pattern[step].attack {
+                        BossAttack::Burst => {
+                            sfx.play_at(SoundId::Burning, self.entity.position);
+                            self.explode(enemies, object_controller);
+                        }
+                        BossAttack::Ring(count) => {
+                            sfx.play_at(SoundId::Burning, self.entity.position);
+                            self.ring(count, enemies, object_controller);
+                        }
+                        BossAttack::FlameRain(count) => {
+                            sfx.play_at(SoundId::Burning, self.entity.position);
+                            self.flame_rain(count, projectiles, object_controller);
+                        }
+                        BossAttack::Dash => {
+                            sfx.play_at(SoundId::BossMove, self.entity.position);
+                            let arena_left = self.arena.camera.x;
+                            let arena_right = self.arena.camera.x + self.arena.size.x;
+                            let target_x =
+                                if self.entity.position.x < arena_left + self.arena.size.x / 2 {
+                                    arena_right - 24
+                                } else {
+                                    arena_left + 24
+                                };
+                            let target = (target_x, player.entity.position.y).into();
+                            self.state = BossActiveState::Dashing(step + 1, target);
+                        }
                     }
                 }
             }
+            BossActiveState::Dashing(step, target) => {
+                let difference = *target - self.entity.position;
+                if difference.manhattan_distance() < 4.into() {
+                    self.entity.velocity = (0, 0).into();
+                    self.state = BossActiveState::Attacking(*step, 0);
+                } else {
+                    self.entity.velocity = difference.normalise() * 3;
+                }
+
+                if self.entity.collider().touches(player.entity.collider()) {
+                    instruction = BossInstruction::DamagePlayer;
+                }
+            }
             BossActiveState::WaitingUntilDamaged(time) => {
                 *time -= 1;
                 if *time == 0 {
-                    sfx.burning();
-                    self.explode(enemies, object_controller);
-                    self.state = BossActiveState::WaitingUntilDamaged(60 * 5);
-                }
-                if let Some(hurt) = &player.hurtbox {
-                    if hurt.touches(self.entity.collider()) {
-                        self.health -= 1;
-                        self.state = BossActiveState::Damaged(30);
+                    self.state = BossActiveState::Attacking(0, 0);
+                }
+            }
+            BossActiveState::Dying(stage, time) => {
+                *time -= 1;
+                // shake violently, then drift upwards, then hang still before handing over to
+                // the healing sequence
+                match (*stage, *time) {
+                    (0, 0) => {
+                        *stage = 1;
+                        *time = 60;
+                        self.entity.velocity = (0.into(), Number::new(-1) / 4).into();
+                        palettes.flash(PaletteTarget::Sprite(1), 0x7FFF, 4, 60);
+                        palettes.cycle(PaletteTarget::Sprite(1), 1..16, 4, 60);
+                        sfx.play_at(SoundId::BossMove, self.entity.position);
+                    }
+                    (1, 0) => {
+                        *stage = 2;
+                        *time = 30;
+                        self.entity.velocity = (0, 0).into();
+                        palettes.flash(PaletteTarget::Sprite(1), 0x7FFF, 0, 30);
+                    }
+                    (2, 0) => {
+                        instruction = BossInstruction::Dead;
+                        self.state = BossActiveState::WaitUntilKilled;
                     }
+                    _ => {}
                 }
             }
             BossActiveState::WaitUntilKilled => {}
@@ -1713,7 +2708,10 @@ impl<'a> Boss<'a> {
             BossActiveState::Damaged(_) => 6,
             BossActiveState::MovingToTarget => 4,
             BossActiveState::WaitingUntilExplosion(_) => 3,
+            BossActiveState::Attacking(_, _) => 5,
+            BossActiveState::Dashing(_, _) => 2,
             BossActiveState::WaitingUntilDamaged(_) => 8,
+            BossActiveState::Dying(stage, _) => 2 + stage as u32 * 4,
             BossActiveState::WaitUntilKilled => 12,
         };
 
@@ -1721,6 +2719,8 @@ impl<'a> Boss<'a> {
             BossActiveState::Damaged(_) => 1.into(),
             BossActiveState::MovingToTarget => 0.into(),
             BossActiveState::WaitingUntilExplosion(_) => 5.into(),
+            BossActiveState::Attacking(_, _) => 2.into(),
+            BossActiveState::Dashing(_, _) => 0.into(),
             BossActiveState::WaitingUntilDamaged(time) => {
                 if time < 60 {
                     5.into()
@@ -1728,8 +2728,12 @@ impl<'a> Boss<'a> {
                     0.into()
                 }
             }
+            BossActiveState::Dying(0, _) => 6.into(),
+            BossActiveState::Dying(1, _) => 3.into(),
+            BossActiveState::Dying(_, _) => 0.into(),
             BossActiveState::WaitUntilKilled => 3.into(),
         };
+
         self.timer += 1;
         let frame = (self.timer / animation_rate) % 12;
         self.entity.sprite.set_tile_id(784 + (frame as u16) * 16);
@@ -1737,29 +2741,40 @@ impl<'a> Boss<'a> {
         self.entity.update_position_without_collision();
         instruction
     }
-    fn commit(&mut self, offset: Vector2D<Number>) {
-        let shake = if self.shake_magnitude != 0.into() {
-            (
-                Number::from_raw(get_random()).rem_euclid(self.shake_magnitude)
-                    - self.shake_magnitude / 2,
-                Number::from_raw(get_random()).rem_euclid(self.shake_magnitude)
-                    - self.shake_magnitude / 2,
-            )
-                .into()
-        } else {
-            (0, 0).into()
-        };
+    // returns true if the attack damaged the boss
+    fn hit(&mut self, attack_id: u16, sfx: &mut sfx::Sfx, palettes: &mut PaletteEffects) -> bool {
+        if self.last_hit_by == Some(attack_id) || self.invulnerable_time > 0 {
+            return false;
+        }
+
+        match self.state {
+            BossActiveState::Attacking(_, _)
+            | BossActiveState::Dashing(_, _)
+            | BossActiveState::WaitingUntilDamaged(_) => {
+                sfx.play_at(SoundId::BossHurt, self.entity.position);
+                self.last_hit_by = Some(attack_id);
+                self.health = self.health.saturating_sub(1);
+                self.invulnerable_time = 60;
+                palettes.flash(PaletteTarget::Sprite(1), 0x7FFF, 0, 12);
+                self.entity.velocity = (0, 0).into();
+                self.state = BossActiveState::Damaged(30);
+                true
+            }
+            _ => false,
+        }
+    }
 
+    fn commit(&mut self, offset: Vector2D<Number>, shake: &Shake) {
         self.entity
-            .commit_with_size(offset + shake, (32, 32).into());
+            .commit_with_size(offset + shake.jitter(self.shake_magnitude), (32, 32).into());
     }
     fn explode(&self, enemies: &mut Arena<Enemy<'a>>, object_controller: &'a ObjectControl) {
-        for _ in 0..(6 - self.health) {
+        for _ in 0..(6 - self.health + self.rules.extra_flames) {
             let x_offset: Number = Number::from_raw(get_random()).rem_euclid(2.into()) - 1;
             let y_offset: Number = Number::from_raw(get_random()).rem_euclid(2.into()) - 1;
             let mut flame = Enemy::new(
                 object_controller,
-                EnemyData::MiniFlame(MiniFlameData::new()),
+                EnemyData::MiniFlame(MiniFlameData::new(self.rules.heal_drop_chance)),
             );
             flame.entity.position = self.entity.position;
             flame.entity.velocity = (x_offset, y_offset).into();
@@ -1767,58 +2782,133 @@ impl<'a> Boss<'a> {
         }
     }
 
-    fn get_next_target_location(&self) -> u8 {
+    fn ring(
+        &self,
+        count: u8,
+        enemies: &mut Arena<Enemy<'a>>,
+        object_controller: &'a ObjectControl,
+    ) {
+        let spacing = RING_DIRECTIONS.len() / count as usize;
+        for &(x, y) in RING_DIRECTIONS.iter().step_by(spacing) {
+            let direction: Vector2D<Number> = (Number::from_raw(x), Number::from_raw(y)).into();
+            let mut flame = Enemy::new(
+                object_controller,
+                EnemyData::MiniFlame(MiniFlameData::new(self.rules.heal_drop_chance)),
+            );
+            flame.entity.position = self.entity.position;
+            flame.entity.velocity = direction * 3 / 2;
+            enemies.insert(flame);
+        }
+    }
+
+    fn flame_rain(
+        &self,
+        count: u8,
+        projectiles: &mut Arena<Projectile<'a>>,
+        object_controller: &'a ObjectControl,
+    ) {
+        let spacing = self.arena.size.x / (count as i32 + 1);
+        for i in 1..=count as i32 {
+            // stagger the heights so the flames don't all land at once
+            let stagger = get_random().rem_euclid(32);
+            let position = self.arena.camera + (spacing * i, Number::new(-8 - stagger)).into();
+            projectiles.insert(Projectile::new(
+                object_controller,
+                ProjectileData::new_flame_rain(240),
+                position,
+                (0, 1).into(),
+            ));
+        }
+    }
+
+    // the boss can still be asked for its pattern on the frame it dies, so health 0 gets the same
+    // pattern as health 1
+    fn pattern(health: u8) -> &'static [AttackStep] {
+        BOSS_PATTERNS[(health as usize).clamp(1, BOSS_PATTERNS.len()) - 1]
+    }
+
+    fn get_next_target_location(&self) -> usize {
+        let waypoint_count = self.arena.waypoints.len();
+        if waypoint_count < 2 {
+            return 0;
+        }
+
         loop {
-            let a = get_random().rem_euclid(5) as u8;
+            let a = get_random().rem_euclid(waypoint_count as i32) as usize;
             if a != self.target_location {
                 break a;
             }
         }
     }
-    fn get_target_location(&self) -> Vector2D<Number> {
-        match self.target_location {
-            0 => (240 / 4, 160 / 4).into(),
-            1 => (3 * 240 / 4, 160 / 4).into(),
-            2 => (240 / 4, 3 * 160 / 4).into(),
-            3 => (3 * 240 / 4, 3 * 160 / 4).into(),
-            4 => (240 / 2, 160 / 2).into(),
-            _ => unreachable!(),
-        }
-    }
 }
 
+// the sky brightens once the boss has been healed
+const SUNRISE: &[Tint] = &[
+    Tint {
+        frame: 0,
+        colours: &[],
+        easing: Easing::Linear,
+    },
+    Tint {
+        frame: 120,
+        colours: &[(0, 17982), (1, 22427)],
+        easing: Easing::EaseInOut,
+    },
+];
+
+// Extra sprite palettes made by swapping around the red, green and blue of the object palette, so
+// enemies which share frames can still be told apart. They come after the object and boss
+// palettes, so the first one is sprite palette 2.
+const ENEMY_PALETTE_SWAPS: &[[usize; 3]] = &[
+    // large slimes
+    [2, 1, 0],
+    // small slimes
+    [1, 0, 2],
+    // climbing slimes
+    [2, 0, 1],
+    // spitters
+    [1, 2, 0],
+];
+
 struct Game<'a> {
     player: Player<'a>,
-    input: ButtonController,
+    input: Input,
     frame_count: u32,
     level: Level,
-    offset: Vector2D<Number>,
-    shake_time: u16,
+    camera: Camera,
+    shake: Shake,
     sunrise_timer: u16,
 
     enemies: Arena<Enemy<'a>>,
     particles: Arena<Particle<'a>>,
+    projectiles: Arena<Projectile<'a>>,
     slime_load: usize,
     bat_load: usize,
     emu_load: usize,
+    spitter_load: usize,
+    scroll_trigger_load: usize,
+    // the scroll to resume once the enemies on screen have been cleared
+    paused_scroll: Option<Vector2D<Number>>,
     boss: BossState<'a>,
-    move_state: MoveState,
-    fade_count: u16,
+    ending: bool,
+    fading_out: bool,
+    palettes: PaletteEffects,
+    settings: Settings,
+    rules: Rules,
+    // the title, pause or options screen, which stops the game while it is open
+    menu: Option<Menu>,
+    // there is only a text layer if the map leaves a background free for it
+    text: Option<TextLayer>,
+    // the time last written by the timer, so it is only redrawn when it changes
+    shown_seconds: Option<u32>,
 
     background_distributor: &'a mut BackgroundDistributor,
 }
 
-enum MoveState {
-    Advancing,
-    PinnedAtEnd,
-    FollowingPlayer,
-    Ending,
-}
-
 impl<'a> Game<'a> {
     fn has_just_reached_end(&self) -> bool {
         match self.boss {
-            BossState::NotSpawned => self.offset.x.floor() + 248 >= tilemap::WIDTH as i32 * 8,
+            BossState::NotSpawned => self.camera.position().x >= self.level.boss_arena.camera.x,
             _ => false,
         }
     }
@@ -1830,53 +2920,124 @@ impl<'a> Game<'a> {
     ) -> GameStatus {
         let mut state = GameStatus::Continue;
 
-        match self.move_state {
-            MoveState::Advancing => {
-                self.offset += Into::<Vector2D<Number>>::into((1, 0)) / 8;
+        self.input.update();
 
-                if self.has_just_reached_end() {
-                    sfx.boss();
-                    self.offset.x = (tilemap::WIDTH as i32 * 8 - 248).into();
-                    self.move_state = MoveState::PinnedAtEnd;
-                    self.boss = BossState::Active(Boss::new(object_controller, self.offset))
+        if let Some(menu) = &mut self.menu {
+            match menu.update(&self.input, &mut self.settings) {
+                MenuStatus::Open => {}
+                MenuStatus::SettingsChanged => {
+                    sfx.set_volume(self.settings.volume());
+                    self.input.set_bindings(Bindings::new(&self.settings));
+                    self.shake.set_enabled(self.settings.screen_shake);
                 }
+                MenuStatus::SettingsDone => self.settings.save(),
+                MenuStatus::Closed => self.menu = None,
             }
-            MoveState::PinnedAtEnd => {
-                self.offset.x = (tilemap::WIDTH as i32 * 8 - 248).into();
+
+            if let Some(text) = &mut self.text {
+                match &self.menu {
+                    Some(menu) => menu.draw(text, &self.settings),
+                    None => text.clear(),
+                }
+                self.shown_seconds = None;
             }
-            MoveState::FollowingPlayer => {
-                Game::update_sunrise(self.background_distributor, self.sunrise_timer);
-                if self.sunrise_timer < 120 {
-                    self.sunrise_timer += 1;
-                } else {
-                    let difference = self.player.entity.position.x - (self.offset.x + WIDTH / 2);
-
-                    self.offset.x += difference / 8;
-                    if self.offset.x > (tilemap::WIDTH as i32 * 8 - 248).into() {
-                        self.offset.x = (tilemap::WIDTH as i32 * 8 - 248).into();
-                    } else if self.offset.x < 8.into() {
-                        self.offset.x = 8.into();
-                        self.move_state = MoveState::Ending;
+
+            self.palettes.update();
+            self.palettes
+                .commit(self.background_distributor, object_controller);
+
+            return state;
+        }
+
+        // the pause menu can't be seen without the text layer, so the game keeps going instead of
+        // freezing on a menu nobody can read
+        if self.input.is_just_pressed(Action::Pause) && self.text.is_some() {
+            // screen shake can be toggled while playing, so that is saved here rather than on
+            // every press, and only if it is different to what was saved last time
+            if Settings::load().screen_shake != self.settings.screen_shake {
+                self.settings.save();
+            }
+            self.menu = Some(Menu::pause());
+        }
+
+        self.camera
+            .update(self.player.entity.position, self.player.entity.velocity);
+        sfx.set_camera(self.camera.position());
+
+        match &mut self.boss {
+            BossState::NotSpawned => {
+                if let Some(velocity) = self.paused_scroll {
+                    let camera = &self.camera;
+                    if !self
+                        .enemies
+                        .iter()
+                        .any(|(_, enemy)| camera.can_see(enemy.entity.position))
+                    {
+                        self.camera.autoscroll(velocity);
+                        self.paused_scroll = None;
+                    }
+                } else if let Some(trigger) =
+                    self.level.scroll_triggers.get(self.scroll_trigger_load)
+                {
+                    if self.camera.has_scrolled_past(trigger.position) {
+                        self.scroll_trigger_load += 1;
+                        let velocity = trigger.velocity * self.rules.scroll_speed;
+                        if trigger.until_clear {
+                            self.camera.autoscroll((0, 0).into());
+                            self.paused_scroll = Some(velocity);
+                        } else {
+                            self.camera.autoscroll(velocity);
+                        }
                     }
                 }
+
+                if self.has_just_reached_end() {
+                    sfx.boss();
+                    self.camera
+                        .pan_to(self.level.boss_arena.camera, Number::new(1) / 2);
+                    self.boss = BossState::Active(Boss::new(
+                        object_controller,
+                        self.level.boss_arena.clone(),
+                        self.rules,
+                    ))
+                }
             }
-            MoveState::Ending => {
-                self.player.controllable = false;
-                if let BossState::Following(boss) = &mut self.boss {
+            BossState::Active(_) => {}
+            BossState::Following(boss) => {
+                if self.ending {
+                    self.player.controllable = false;
                     boss.to_hole = true;
-                    if boss.gone {
-                        self.fade_count += 1;
-                        self.fade_count = self.fade_count.min(600);
-                        Game::update_fade_out(self.background_distributor, self.fade_count);
+                    if boss.gone && !self.fading_out {
+                        self.fading_out = true;
+                        self.palettes.fade_to(
+                            PaletteTarget::Background(0),
+                            0..3,
+                            0x7FFF,
+                            600,
+                            Easing::EaseIn,
+                        );
+                    }
+                } else {
+                    if self.sunrise_timer < 120 {
+                        self.sunrise_timer += 1;
+                        if self.sunrise_timer == 120 {
+                            self.camera.follow();
+                        }
+                    } else if self.camera.is_at_left_edge() {
+                        self.ending = true;
                     }
                 }
             }
         }
 
-        match self
-            .boss
-            .update(&mut self.enemies, object_controller, &self.player, sfx)
-        {
+        match self.boss.update(
+            &mut self.enemies,
+            &mut self.projectiles,
+            object_controller,
+            &self.player,
+            sfx,
+            &mut self.palettes,
+        ) {
             BossInstruction::Dead => {
                 let boss = match &self.boss {
                     BossState::Active(b) => b,
@@ -1891,35 +3052,43 @@ impl<'a> Game<'a> {
                 sfx.stop_music();
                 self.player.sword = SwordState::Swordless;
             }
+            BossInstruction::DamagePlayer => {
+                let (alive, damaged) = self.player.damage();
+                if !alive {
+                    state = GameStatus::Lost;
+                }
+                if damaged {
+                    sfx.play(SoundId::PlayerHurt);
+                    self.shake.player_hurt();
+                }
+            }
+            BossInstruction::Explode => {
+                self.shake.boss_explode();
+            }
             BossInstruction::None => {}
         }
 
         self.load_enemies(object_controller);
+        sfx.set_intensity(self.music_intensity());
 
-        if self.player.entity.position.x < self.offset.x - 8 {
+        if self.camera.is_behind(self.player.entity.position) {
             let (alive, damaged) = self.player.damage();
             if !alive {
                 state = GameStatus::Lost;
             }
             if damaged {
-                sfx.player_hurt();
-                self.shake_time += 20;
+                sfx.play(SoundId::PlayerHurt);
+                self.shake.player_hurt();
             }
         }
 
-        let mut this_frame_offset = self.offset;
-        if self.shake_time > 0 {
-            let size = self.shake_time.min(4) as i32;
-            let offset: Vector2D<Number> = (
-                Number::from_raw(get_random()) % size - Number::new(size) / 2,
-                Number::from_raw(get_random()) % size - Number::new(size) / 2,
-            )
-                .into();
-            this_frame_offset += offset;
-            self.shake_time -= 1;
-        }
+        let this_frame_offset = self.camera.position() + self.shake.offset();
+        self.shake.update();
 
-        self.input.update();
+        if self.input.is_just_pressed(Action::ToggleShake) {
+            self.settings.screen_shake = !self.settings.screen_shake;
+            self.shake.set_enabled(self.settings.screen_shake);
+        }
         match self.player.update(&self.input, &self.level, sfx) {
             UpdateInstruction::CreateParticle(data, position) => {
                 let new_particle = Particle::new(object_controller, data, position);
@@ -1930,58 +3099,152 @@ impl<'a> Game<'a> {
         }
 
         let mut remove = Vec::with_capacity(10);
+        let mut instructions = Vec::with_capacity(10);
         for (idx, enemy) in self.enemies.iter_mut() {
-            if enemy.entity.position.x < self.offset.x - 8 {
+            if self.camera.is_behind(enemy.entity.position) {
                 remove.push(idx);
                 continue;
             }
 
-            match enemy.update(&self.player, &self.level, sfx) {
+            instructions.push((idx, enemy.update(&self.player, &self.level, sfx)));
+        }
+
+        let mut hits = self.resolve_player_attack(sfx);
+
+        let mut remove_projectiles = Vec::with_capacity(10);
+        for (idx, projectile) in self.projectiles.iter_mut() {
+            match projectile.update(&self.player, &self.level) {
+                UpdateInstruction::Remove => {
+                    remove_projectiles.push(idx);
+                    continue;
+                }
+                UpdateInstruction::DamagePlayer => {
+                    let (alive, damaged) = self.player.damage();
+                    if !alive {
+                        state = GameStatus::Lost;
+                    }
+                    if damaged {
+                        sfx.play(SoundId::PlayerHurt);
+                        self.shake.player_hurt();
+                    }
+                }
+                _ => {}
+            }
+
+            // projectiles which have been knocked back by the sword hit the first enemy they touch
+            if projectile.projectile_data.reflected {
+                let collider = projectile.entity.collider();
+                let hit = self.enemies.iter_mut().find_map(|(enemy_idx, enemy)| {
+                    if collider.touches(enemy.entity.collider()) {
+                        enemy.hit(sfx).map(|instruction| (enemy_idx, instruction))
+                    } else {
+                        None
+                    }
+                });
+
+                if let Some(hit) = hit {
+                    hits.push(hit);
+                    remove_projectiles.push(idx);
+                }
+            }
+        }
+
+        // enemies which were struck down this frame don't get to hurt the player
+        for (idx, instruction) in instructions.iter_mut() {
+            if hits.iter().any(|(hit_idx, _)| hit_idx == idx) {
+                match instruction {
+                    UpdateInstruction::DamagePlayer => *instruction = UpdateInstruction::None,
+                    UpdateInstruction::DamagePlayerAndCrash(direction) => {
+                        *instruction = UpdateInstruction::Crash(*direction)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        instructions.extend(hits);
+
+        for (idx, instruction) in instructions {
+            match instruction {
                 UpdateInstruction::Remove => {
                     remove.push(idx);
                 }
                 UpdateInstruction::HealPlayerAndRemove => {
                     self.player.heal();
-                    sfx.player_heal();
+                    sfx.play(SoundId::PlayerHeal);
                     remove.push(idx);
                 }
                 UpdateInstruction::HealBossAndRemove => {}
-                UpdateInstruction::DamagePlayer => {
+                UpdateInstruction::DamagePlayer | UpdateInstruction::DamagePlayerAndCrash(_) => {
                     let (alive, damaged) = self.player.damage();
                     if !alive {
                         state = GameStatus::Lost;
                     }
                     if damaged {
-                        sfx.player_hurt();
-                        self.shake_time += 20;
+                        sfx.play(SoundId::PlayerHurt);
+                        self.shake.player_hurt();
+                    }
+                    // the crash adds its trauma and kick on top of the hurt shake rather than
+                    // replacing it
+                    if let UpdateInstruction::DamagePlayerAndCrash(direction) = instruction {
+                        self.shake.emu_crash(direction);
                     }
                 }
                 UpdateInstruction::CreateParticle(data, position) => {
                     let new_particle = Particle::new(object_controller, data, position);
                     self.particles.insert(new_particle);
                 }
+                UpdateInstruction::CreateEnemy(data, position, velocity) => {
+                    let mut enemy = Enemy::new(object_controller, data);
+                    enemy.entity.position = position;
+                    enemy.entity.velocity = velocity;
+                    let piece = self.enemies.insert(enemy);
+
+                    // the pieces of something the current swing has hit can't be hit by the same
+                    // swing, otherwise a whole splitting slime goes down in one attack
+                    if let Some(attack) = &mut self.player.attack {
+                        if attack.targets_hit.contains(&HitTarget::Enemy(idx)) {
+                            attack.record_hit(HitTarget::Enemy(piece));
+                        }
+                    }
+                }
+                UpdateInstruction::CreateProjectile(data, position, velocity) => {
+                    let new_projectile =
+                        Projectile::new(object_controller, data, position, velocity);
+                    self.projectiles.insert(new_projectile);
+                }
+                UpdateInstruction::Crash(direction) => {
+                    self.shake.emu_crash(direction);
+                }
                 UpdateInstruction::None => {}
             }
+        }
+
+        for (_, enemy) in self.enemies.iter_mut() {
             enemy
                 .entity
                 .commit_with_fudge(this_frame_offset, (0, 0).into());
         }
 
+        for (_, projectile) in self.projectiles.iter_mut() {
+            projectile
+                .entity
+                .commit_with_fudge(this_frame_offset, (0, 0).into());
+        }
+
+        for i in remove_projectiles {
+            self.projectiles.remove(i);
+        }
+
         self.player.commit(this_frame_offset);
-        self.boss.commit(this_frame_offset);
-
-        self.level
-            .background
-            .set_position(this_frame_offset.floor());
-        self.level
-            .foreground
-            .set_position(this_frame_offset.floor());
-        self.level
-            .clouds
-            .set_position(this_frame_offset.floor() / 4);
-        self.level.background.commit();
-        self.level.foreground.commit();
-        self.level.clouds.commit();
+        self.boss.commit(this_frame_offset, &self.shake);
+
+        for layer in self.level.layers.iter_mut() {
+            layer.commit(this_frame_offset);
+        }
+
+        self.palettes.update();
+        self.palettes
+            .commit(self.background_distributor, object_controller);
 
         for i in remove {
             self.enemies.remove(i);
@@ -2000,12 +3263,12 @@ impl<'a> Game<'a> {
                     };
                     self.boss =
                         BossState::Following(FollowingBoss::new(object_controller, location));
-                    self.move_state = MoveState::FollowingPlayer;
+                    self.palettes.tint(PaletteTarget::Background(0), SUNRISE);
                     remove.push(idx);
                 }
                 UpdateInstruction::HealPlayerAndRemove => {
                     self.player.heal();
-                    sfx.player_heal();
+                    sfx.play(SoundId::PlayerHeal);
                     remove.push(idx);
                 }
                 UpdateInstruction::DamagePlayer => {
@@ -2014,11 +3277,15 @@ impl<'a> Game<'a> {
                         state = GameStatus::Lost;
                     }
                     if damaged {
-                        sfx.player_hurt();
-                        self.shake_time += 20;
+                        sfx.play(SoundId::PlayerHurt);
+                        self.shake.player_hurt();
                     }
                 }
                 UpdateInstruction::CreateParticle(_, _) => {}
+                UpdateInstruction::CreateEnemy(_, _, _) => {}
+                UpdateInstruction::CreateProjectile(_, _, _) => {}
+                UpdateInstruction::DamagePlayerAndCrash(_) => {}
+                UpdateInstruction::Crash(_) => {}
                 UpdateInstruction::None => {}
             }
             particle
@@ -2031,6 +3298,16 @@ impl<'a> Game<'a> {
         }
 
         self.frame_count += 1;
+
+        if let Some(text) = &mut self.text {
+            let seconds = self.frame_count / 60;
+            if self.settings.show_timer && self.shown_seconds != Some(seconds) {
+                let mut buffer = [0; text::TIME_LENGTH];
+                let time = text::format_time(seconds, &mut buffer);
+                text.write(text::COLUMNS - 1 - time.len(), 1, time);
+                self.shown_seconds = Some(seconds);
+            }
+        }
         if let GameStatus::Lost = state {
             match self.boss {
                 BossState::Active(_) => GameStatus::RespawnAtBoss,
@@ -2041,6 +3318,84 @@ impl<'a> Game<'a> {
         }
     }
 
+    // Works out what the player's current swing has hit. This happens once all the enemies have
+    // moved so that the result doesn't depend on the order they are updated in.
+    fn resolve_player_attack(&mut self, sfx: &mut sfx::Sfx) -> Vec<(Index, UpdateInstruction)> {
+        let mut hits = Vec::new();
+
+        let (hurtbox, attack) = match (&self.player.hurtbox, &mut self.player.attack) {
+            (Some(hurtbox), Some(attack)) => (hurtbox, attack),
+            _ => return hits,
+        };
+        let mut candidates: Vec<HitTarget> = self
+            .enemies
+            .iter()
+            .filter(|(_, enemy)| hurtbox.touches(enemy.entity.collider()))
+            .map(|(idx, _)| HitTarget::Enemy(idx))
+            .collect();
+
+        if let BossState::Active(boss) = &self.boss {
+            if hurtbox.touches(boss.entity.collider()) {
+                candidates.push(HitTarget::Boss);
+            }
+        }
+
+        let mut landed = false;
+
+        for target in candidates {
+            if !attack.can_hit(target) {
+                continue;
+            }
+
+            let was_hit = match target {
+                HitTarget::Enemy(idx) => match self.enemies[idx].hit(sfx) {
+                    Some(instruction) => {
+                        hits.push((idx, instruction));
+                        true
+                    }
+                    None => false,
+                },
+                HitTarget::Boss => match &mut self.boss {
+                    BossState::Active(boss) => boss.hit(attack.id, sfx, &mut self.palettes),
+                    _ => false,
+                },
+            };
+
+            if was_hit {
+                attack.record_hit(target);
+                landed = true;
+            }
+        }
+
+        // one confirmation for the whole swing, however many things it hit
+        if landed {
+            sfx.play(SoundId::SwordHit);
+        }
+
+        hits
+    }
+
+    // from 0 to 3, going up as more enemies are around, the player's sword gets shorter and the
+    // boss gets closer to being healed
+    fn music_intensity(&self) -> u8 {
+        let danger = match &self.boss {
+            BossState::Active(boss) => {
+                if boss.health <= 2 {
+                    3
+                } else {
+                    2
+                }
+            }
+            _ => (self.enemies.len() >= 4) as u8,
+        };
+
+        // the sword gets shorter each time the player is hurt, and one more hit with the dagger
+        // is fatal
+        let low_on_health = self.player.sword == SwordState::Dagger;
+
+        (danger + low_on_health as u8).min(3)
+    }
+
     fn load_enemies(&mut self, object_controller: &'a ObjectControl) {
         if self.slime_load < self.level.slime_spawns.len() {
             for (idx, slime_spawn) in self
@@ -2050,29 +3405,39 @@ impl<'a> Game<'a> {
                 .enumerate()
                 .skip(self.slime_load)
             {
-                if slime_spawn.0 as i32 > self.offset.x.floor() + 300 {
+                if slime_spawn.0 as i32 > self.camera.position().x.floor() + 300 {
                     break;
                 }
                 self.slime_load = idx + 1;
-                let mut slime = Enemy::new(object_controller, EnemyData::Slime(SlimeData::new()));
+                let variant = self.level.slime_variants[idx];
+                let mut slime =
+                    Enemy::new(object_controller, EnemyData::Slime(SlimeData::new(variant)));
                 slime.entity.position = (slime_spawn.0 as i32, slime_spawn.1 as i32 - 7).into();
                 self.enemies.insert(slime);
             }
         }
         if self.bat_load < self.level.bat_spawns.len() {
             for (idx, bat_spawn) in self.level.bat_spawns.iter().enumerate().skip(self.bat_load) {
-                if bat_spawn.0 as i32 > self.offset.x.floor() + 300 {
+                if bat_spawn.0 as i32 > self.camera.position().x.floor() + 300 {
                     break;
                 }
                 self.bat_load = idx + 1;
-                let mut bat = Enemy::new(object_controller, EnemyData::Bat(BatData::new()));
-                bat.entity.position = (bat_spawn.0 as i32, bat_spawn.1 as i32).into();
+                let home = (bat_spawn.0 as i32, bat_spawn.1 as i32).into();
+                let patrol = self.level.bat_patrols[idx]
+                    .iter()
+                    .map(|waypoint| (waypoint.0 as i32, waypoint.1 as i32).into())
+                    .collect();
+                let mut bat = Enemy::new(
+                    object_controller,
+                    EnemyData::Bat(BatData::new(home, patrol)),
+                );
+                bat.entity.position = home;
                 self.enemies.insert(bat);
             }
         }
         if self.emu_load < self.level.emu_spawns.len() {
             for (idx, emu_spawn) in self.level.emu_spawns.iter().enumerate().skip(self.emu_load) {
-                if emu_spawn.0 as i32 > self.offset.x.floor() + 300 {
+                if emu_spawn.0 as i32 > self.camera.position().x.floor() + 300 {
                     break;
                 }
                 self.emu_load = idx + 1;
@@ -2081,34 +3446,25 @@ impl<'a> Game<'a> {
                 self.enemies.insert(emu);
             }
         }
-    }
-
-    fn update_sunrise(background_distributor: &'a mut BackgroundDistributor, time: u16) {
-        let mut modified_palette = background::background.palettes[0].clone();
-
-        let a = modified_palette.get_colour(0);
-        let b = modified_palette.get_colour(1);
-
-        modified_palette.update_colour(0, interpolate_colour(a, 17982, time, 120));
-        modified_palette.update_colour(1, interpolate_colour(b, 22427, time, 120));
-
-        let modified_palettes = [modified_palette];
-
-        background_distributor.set_background_palettes(&modified_palettes);
-    }
-
-    fn update_fade_out(background_distributor: &'a mut BackgroundDistributor, time: u16) {
-        let mut modified_palette = background::background.palettes[0].clone();
-
-        let c = modified_palette.get_colour(2);
-
-        modified_palette.update_colour(0, interpolate_colour(17982, 0x7FFF, time, 600));
-        modified_palette.update_colour(1, interpolate_colour(22427, 0x7FFF, time, 600));
-        modified_palette.update_colour(2, interpolate_colour(c, 0x7FFF, time, 600));
-
-        let modified_palettes = [modified_palette];
-
-        background_distributor.set_background_palettes(&modified_palettes);
+        if self.spitter_load < self.level.spitter_spawns.len() {
+            for (idx, spitter_spawn) in self
+                .level
+                .spitter_spawns
+                .iter()
+                .enumerate()
+                .skip(self.spitter_load)
+            {
+                if spitter_spawn.0 as i32 > self.camera.position().x.floor() + 300 {
+                    break;
+                }
+                self.spitter_load = idx + 1;
+                let mut spitter =
+                    Enemy::new(object_controller, EnemyData::Spitter(SpitterData::new()));
+                spitter.entity.position =
+                    (spitter_spawn.0 as i32, spitter_spawn.1 as i32 - 7).into();
+                self.enemies.insert(spitter);
+            }
+        }
     }
 
     fn new(
@@ -2116,30 +3472,69 @@ impl<'a> Game<'a> {
         level: Level,
         background_distributor: &'a mut BackgroundDistributor,
         start_at_boss: bool,
+        settings: Settings,
+        text: Option<TextLayer>,
+        show_title: bool,
     ) -> Self {
-        let mut player = Player::new(object);
+        let rules = settings.difficulty.rules();
+        let mut player = Player::new(object, rules);
         let mut offset = (8, 8).into();
         if start_at_boss {
-            player.entity.position = (133 * 8, 10 * 8).into();
-            offset = (130 * 8, 8).into();
+            // respawn just before the arena so the boss fight starts straight away
+            offset = level.boss_arena.camera - (32, 0).into();
+            player.entity.position = offset + (24, 72).into();
         }
+        let mut camera = Camera::new(
+            offset,
+            Camera::bounds_for_map(tilemap::WIDTH, tilemap::HEIGHT),
+        );
+        camera.autoscroll(Into::<Vector2D<Number>>::into((1, 0)) / 8 * rules.scroll_speed);
+
+        let object_palette = &objects::objects.palettes[0];
+        let enemy_palettes: Vec<Palette16> = ENEMY_PALETTE_SWAPS
+            .iter()
+            .map(|&order| palette::swap_channels(object_palette, order))
+            .collect();
+        let sprite_palettes: Vec<&Palette16> = [object_palette, &objects::boss.palettes[0]]
+            .iter()
+            .copied()
+            .chain(enemy_palettes.iter())
+            .collect();
+
+        let palettes = PaletteEffects::new(background::background.palettes, &sprite_palettes);
+
         Self {
             player,
-            input: ButtonController::new(),
+            input: Input::new(Bindings::new(&settings)),
             frame_count: 0,
             level,
-            offset,
-            shake_time: 0,
+            camera,
+            shake: Shake::new(settings.screen_shake),
 
             enemies: Arena::with_capacity(100),
             slime_load: 0,
             bat_load: 0,
             emu_load: 0,
+            spitter_load: 0,
+            scroll_trigger_load: 0,
+            paused_scroll: None,
             particles: Arena::with_capacity(30),
+            projectiles: Arena::with_capacity(20),
             boss: BossState::NotSpawned,
-            move_state: MoveState::Advancing,
+            ending: false,
             sunrise_timer: 0,
-            fade_count: 0,
+            fading_out: false,
+            palettes,
+            settings,
+            rules,
+            // the menus can't be seen without the text layer, so the title is skipped
+            menu: if show_title && text.is_some() {
+                Some(Menu::title())
+            } else {
+                None
+            },
+            text,
+            shown_seconds: None,
 
             background_distributor,
         }
@@ -2163,33 +3558,52 @@ fn game_with_level(gba: &mut agb::Gba) {
     let mut mixer = gba.mixer.mixer();
     mixer.enable();
 
-    let mut sfx = sfx::Sfx::new(&mut mixer);
+    let mut settings = Settings::load();
+
+    let mut sfx = sfx::Sfx::new(&mut mixer, settings.volume());
     sfx.purple_night();
 
     let mut start_at_boss = false;
+    // the title screen is only shown when the game is first turned on
+    let mut show_title = true;
 
     loop {
         let mut background = gba.display.video.tiled0();
         background.set_background_palettes(background::background.palettes);
-        background.set_background_tilemap(0, background::background.tiles);
+        text::load_tiles(&mut background, background::background.tiles);
         let mut object = gba.display.object.get();
         object.enable();
 
+        let level = Level::load_level(
+            (0..tilemap::LAYER_MAPS.len())
+                .map(|_| background.get_regular().unwrap())
+                .collect(),
+        );
+        assert!(
+            background::background.palettes.len() <= text::PALETTE as usize,
+            "the level's palettes must leave the last one free for the font"
+        );
+        let text = background
+            .get_regular()
+            .map(|text| TextLayer::new(text, &mut background));
+
         let mut game = Game::new(
             &object,
-            Level::load_level(
-                background.get_regular().unwrap(),
-                background.get_regular().unwrap(),
-                background.get_regular().unwrap(),
-            ),
+            level,
             &mut background,
             start_at_boss,
+            settings,
+            text,
+            show_title,
         );
+        show_title = false;
 
         start_at_boss = loop {
             vblank.wait_for_vblank();
             sfx.vblank();
-            match game.advance_frame(&object, &mut sfx) {
+            let status = game.advance_frame(&object, &mut sfx);
+            settings = game.settings;
+            match status {
                 GameStatus::Continue => {}
                 GameStatus::Lost | GameStatus::Won => {
                     break false;
@@ -2226,24 +3640,3 @@ fn ping_pong(i: u16, n: u16) -> u16 {
         i
     }
 }
-
-fn interpolate_colour(initial: u16, destination: u16, time_so_far: u16, total_time: u16) -> u16 {
-    const MASK: u16 = 0b11111;
-    fn to_components(c: u16) -> [u16; 3] {
-        [c & MASK, (c >> 5) & MASK, (c >> 10) & MASK]
-    }
-
-    let initial_rgb = to_components(initial);
-    let destination_rgb = to_components(destination);
-    let mut colour = 0;
-
-    for (i, c) in initial_rgb
-        .iter()
-        .zip(destination_rgb)
-        .map(|(a, b)| (b - a) * time_so_far / total_time + a)
-        .enumerate()
-    {
-        colour |= (c & MASK) << (i * 5);
-    }
-    colour
-}
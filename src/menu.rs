@@ -0,0 +1,176 @@
+use crate::input::{Action, Input};
+use crate::settings::{Difficulty, Settings, MAX_VOLUME};
+use crate::text::TextLayer;
+
+const TITLE_ITEMS: &[&str] = &["PLAY", "OPTIONS"];
+const PAUSE_ITEMS: &[&str] = &["RESUME", "OPTIONS"];
+const OPTION_ITEMS: &[&str] = &[
+    "MUSIC",
+    "SOUND",
+    "SCREEN SHAKE",
+    "JUMP",
+    "ATTACK",
+    "DIFFICULTY",
+    "SHOW TIMER",
+    "BACK",
+];
+const BUTTON_NAMES: [&str; 4] = ["A", "B", "L", "R"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Screen {
+    Title,
+    Pause,
+    // remembers which screen to go back to
+    Options(bool),
+}
+
+pub enum MenuStatus {
+    Open,
+    // the settings have been changed and should be applied straight away
+    SettingsChanged,
+    // the options have been closed, so the settings should be saved
+    SettingsDone,
+    Closed,
+}
+
+pub struct Menu {
+    screen: Screen,
+    cursor: usize,
+}
+
+impl Menu {
+    pub fn title() -> Self {
+        Self {
+            screen: Screen::Title,
+            cursor: 0,
+        }
+    }
+
+    pub fn pause() -> Self {
+        Self {
+            screen: Screen::Pause,
+            cursor: 0,
+        }
+    }
+
+    fn items(&self) -> &'static [&'static str] {
+        match self.screen {
+            Screen::Title => TITLE_ITEMS,
+            Screen::Pause => PAUSE_ITEMS,
+            Screen::Options(_) => OPTION_ITEMS,
+        }
+    }
+
+    pub fn update(&mut self, input: &Input, settings: &mut Settings) -> MenuStatus {
+        let item_count = self.items().len();
+        if input.is_just_pressed(Action::Up) {
+            self.cursor = (self.cursor + item_count - 1) % item_count;
+        }
+        if input.is_just_pressed(Action::Down) {
+            self.cursor = (self.cursor + 1) % item_count;
+        }
+
+        let confirm =
+            input.is_just_pressed(Action::Confirm) || input.is_just_pressed(Action::Pause);
+
+        match self.screen {
+            Screen::Title | Screen::Pause => {
+                let resume = self.screen == Screen::Pause && input.is_just_pressed(Action::Back);
+
+                if resume || (confirm && self.cursor == 0) {
+                    return MenuStatus::Closed;
+                }
+
+                if confirm {
+                    self.screen = Screen::Options(self.screen == Screen::Title);
+                    self.cursor = 0;
+                }
+
+                MenuStatus::Open
+            }
+            Screen::Options(from_title) => {
+                if input.is_just_pressed(Action::Back)
+                    || (confirm && self.cursor == OPTION_ITEMS.len() - 1)
+                {
+                    self.screen = if from_title {
+                        Screen::Title
+                    } else {
+                        Screen::Pause
+                    };
+                    self.cursor = 1;
+                    return MenuStatus::SettingsDone;
+                }
+
+                let direction = if input.is_just_pressed(Action::Left) {
+                    -1
+                } else if input.is_just_pressed(Action::Right) || confirm {
+                    1
+                } else {
+                    return MenuStatus::Open;
+                };
+
+                let step_volume =
+                    |volume: u8| (volume as i32 + direction).clamp(0, MAX_VOLUME as i32) as u8;
+
+                match self.cursor {
+                    0 => settings.music_volume = step_volume(settings.music_volume),
+                    1 => settings.sfx_volume = step_volume(settings.sfx_volume),
+                    2 => settings.screen_shake = !settings.screen_shake,
+                    3 => settings.next_jump_button(direction),
+                    4 => settings.next_attack_button(direction),
+                    5 => settings.next_difficulty(direction),
+                    6 => settings.show_timer = !settings.show_timer,
+                    _ => return MenuStatus::Open,
+                }
+
+                MenuStatus::SettingsChanged
+            }
+        }
+    }
+
+    pub fn draw(&self, text: &mut TextLayer, settings: &Settings) {
+        text.clear();
+
+        let heading = match self.screen {
+            Screen::Title => "THE PURPLE NIGHT",
+            Screen::Pause => "PAUSED",
+            Screen::Options(_) => "OPTIONS",
+        };
+        text.write(15 - heading.len() / 2, 2, heading);
+
+        // the options only just fit on the screen
+        let top = match self.screen {
+            Screen::Options(_) => 5,
+            _ => 6,
+        };
+        for (i, item) in self.items().iter().enumerate() {
+            if i == self.cursor {
+                text.write(3, top + i * 2, ">");
+            }
+            text.write(5, top + i * 2, item);
+        }
+
+        if let Screen::Options(_) = self.screen {
+            let volume = |volume: u8| &"012345678"[volume as usize..volume as usize + 1];
+            let on_off = |on: bool| if on { "ON" } else { "OFF" };
+
+            let values = [
+                volume(settings.music_volume),
+                volume(settings.sfx_volume),
+                on_off(settings.screen_shake),
+                BUTTON_NAMES[settings.jump_button],
+                BUTTON_NAMES[settings.attack_button],
+                match settings.difficulty {
+                    Difficulty::Easy => "EASY",
+                    Difficulty::Normal => "NORMAL",
+                    Difficulty::Hard => "HARD",
+                },
+                on_off(settings.show_timer),
+            ];
+
+            for (i, value) in values.iter().enumerate() {
+                text.write(19, top + i * 2, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use core::ops::Range;
+
+use alloc::vec::Vec;
+
+use agb::display::{
+    background::BackgroundDistributor, object::ObjectControl, palette16::Palette16,
+};
+
+use crate::colour::{interpolate_colour, Easing};
+
+// A copy of the palette with the red, green and blue of every colour moved around, so the same
+// sprites can be drawn in a different colour. `order` says which of red, green and blue ends up in
+// each of them.
+pub fn swap_channels(palette: &Palette16, order: [usize; 3]) -> Palette16 {
+    let mut swapped = palette.clone();
+
+    for i in 0..16 {
+        let colour = palette.get_colour(i);
+        let channel = |c: usize| (colour >> (c * 5)) & 0b11111;
+        swapped.update_colour(
+            i,
+            channel(order[0]) | (channel(order[1]) << 5) | (channel(order[2]) << 10),
+        );
+    }
+
+    swapped
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PaletteTarget {
+    Background(usize),
+    Sprite(usize),
+}
+
+// A point on a tint curve, giving the colours at that frame and how to ease into them from the
+// previous point. Colours which aren't listed are left as they are in the original palette.
+pub struct Tint {
+    pub frame: u16,
+    pub colours: &'static [(usize, u16)],
+    pub easing: Easing,
+}
+
+enum EffectKind {
+    // fades from what the colours would otherwise be to the given colour, then stays there
+    FadeTo(u16, Easing),
+    // starts at the given colour and fades back to what the colours would otherwise be
+    FadeFrom(u16, Easing),
+    // moves every colour along by one each time the given number of frames passes
+    Cycle(u16),
+    // replaces the colours with the given colour, turning it on and off every period frames
+    // unless the period is 0
+    Flash(u16, u16),
+    // follows the tint curve, then stays at the end of it
+    Tint(&'static [Tint]),
+}
+
+struct Effect {
+    target: PaletteTarget,
+    colours: Range<usize>,
+    kind: EffectKind,
+    time: u16,
+    duration: u16,
+}
+
+impl Effect {
+    fn apply(&self, palette: &mut Palette16) {
+        let colours = self.colours.clone();
+
+        match self.kind {
+            EffectKind::FadeTo(colour, easing) => {
+                for i in colours {
+                    let c = palette.get_colour(i);
+                    palette.update_colour(
+                        i,
+                        interpolate_colour(c, colour, self.time, self.duration, easing),
+                    );
+                }
+            }
+            EffectKind::FadeFrom(colour, easing) => {
+                for i in colours {
+                    let c = palette.get_colour(i);
+                    palette.update_colour(
+                        i,
+                        interpolate_colour(colour, c, self.time, self.duration, easing),
+                    );
+                }
+            }
+            EffectKind::Cycle(step) => {
+                let original = palette.clone();
+                let length = colours.len();
+                let shift = (self.time / step) as usize;
+
+                for (n, i) in colours.clone().enumerate() {
+                    let from = colours.start + (n + shift) % length;
+                    palette.update_colour(i, original.get_colour(from));
+                }
+            }
+            EffectKind::Flash(colour, period) => {
+                if period == 0 || (self.time / period) % 2 == 0 {
+                    for i in colours {
+                        palette.update_colour(i, colour);
+                    }
+                }
+            }
+            EffectKind::Tint(curve) => {
+                let next = curve
+                    .iter()
+                    .position(|key| key.frame > self.time)
+                    .unwrap_or(curve.len());
+
+                if next == 0 || next == curve.len() {
+                    let key = &curve[next.saturating_sub(1)];
+                    for &(i, colour) in key.colours {
+                        palette.update_colour(i, colour);
+                    }
+                    return;
+                }
+
+                let (from, to) = (&curve[next - 1], &curve[next]);
+                let original = palette.clone();
+                let colour_at = |key: &Tint, i: usize| {
+                    key.colours
+                        .iter()
+                        .find(|(index, _)| *index == i)
+                        .map(|(_, colour)| *colour)
+                        .unwrap_or_else(|| original.get_colour(i))
+                };
+
+                for &(i, _) in from.colours.iter().chain(to.colours) {
+                    palette.update_colour(
+                        i,
+                        interpolate_colour(
+                            colour_at(from, i),
+                            colour_at(to, i),
+                            self.time - from.frame,
+                            to.frame - from.frame,
+                            to.easing,
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    // fades and tints hold on their final colours, everything else goes away once it's done
+    fn is_finished(&self) -> bool {
+        match self.kind {
+            EffectKind::FadeTo(_, _) | EffectKind::Tint(_) => false,
+            _ => self.time >= self.duration,
+        }
+    }
+}
+
+// Palette effects are applied in the order they were started on top of the original palettes, and
+// the result is copied to the hardware once a frame.
+pub struct PaletteEffects {
+    effects: Vec<Effect>,
+    background: &'static [Palette16],
+    sprites: Vec<Palette16>,
+    needs_commit: bool,
+}
+
+impl PaletteEffects {
+    pub fn new(background: &'static [Palette16], sprites: &[&Palette16]) -> Self {
+        Self {
+            effects: Vec::new(),
+            background,
+            sprites: sprites.iter().map(|&palette| palette.clone()).collect(),
+            needs_commit: true,
+        }
+    }
+
+    fn start(
+        &mut self,
+        target: PaletteTarget,
+        colours: Range<usize>,
+        kind: EffectKind,
+        duration: u16,
+    ) {
+        self.effects.push(Effect {
+            target,
+            colours,
+            kind,
+            time: 0,
+            duration: duration.max(1),
+        });
+    }
+
+    pub fn fade_to(
+        &mut self,
+        target: PaletteTarget,
+        colours: Range<usize>,
+        colour: u16,
+        duration: u16,
+        easing: Easing,
+    ) {
+        self.start(
+            target,
+            colours,
+            EffectKind::FadeTo(colour, easing),
+            duration,
+        );
+    }
+
+    // nothing fades in at the moment, but this is kept alongside fade_to for when something does
+    #[allow(dead_code)]
+    pub fn fade_from(
+        &mut self,
+        target: PaletteTarget,
+        colours: Range<usize>,
+        colour: u16,
+        duration: u16,
+        easing: Easing,
+    ) {
+        self.start(
+            target,
+            colours,
+            EffectKind::FadeFrom(colour, easing),
+            duration,
+        );
+    }
+
+    pub fn cycle(
+        &mut self,
+        target: PaletteTarget,
+        colours: Range<usize>,
+        step: u16,
+        duration: u16,
+    ) {
+        self.start(target, colours, EffectKind::Cycle(step.max(1)), duration);
+    }
+
+    // colour 0 is transparent for sprites, so flashes start at colour 1
+    pub fn flash(&mut self, target: PaletteTarget, colour: u16, period: u16, duration: u16) {
+        self.start(target, 1..16, EffectKind::Flash(colour, period), duration);
+    }
+
+    pub fn tint(&mut self, target: PaletteTarget, curve: &'static [Tint]) {
+        let duration = curve.last().map(|key| key.frame).unwrap_or(0);
+        self.start(target, 0..16, EffectKind::Tint(curve), duration);
+    }
+
+    pub fn update(&mut self) {
+        if !self.effects.is_empty() {
+            self.needs_commit = true;
+        }
+
+        for effect in self.effects.iter_mut() {
+            effect.time = (effect.time + 1).min(effect.duration);
+        }
+
+        self.effects.retain(|effect| !effect.is_finished());
+    }
+
+    pub fn commit(
+        &mut self,
+        background_distributor: &mut BackgroundDistributor,
+        object_controller: &ObjectControl,
+    ) {
+        if !self.needs_commit {
+            return;
+        }
+        self.needs_commit = false;
+
+        let mut background: Vec<Palette16> = self.background.to_vec();
+        let mut sprites = self.sprites.clone();
+
+        for effect in self.effects.iter() {
+            let palette = match effect.target {
+                PaletteTarget::Background(i) => background.get_mut(i),
+                PaletteTarget::Sprite(i) => sprites.get_mut(i),
+            };
+
+            if let Some(palette) = palette {
+                effect.apply(palette);
+            }
+        }
+
+        background_distributor.set_background_palettes(&background);
+        object_controller.set_sprite_palettes(&sprites);
+    }
+}
@@ -0,0 +1,212 @@
+use agb::input::Button;
+use agb::number::Num;
+
+use crate::sfx::Volume;
+use crate::Number;
+
+// settings are kept in the cartridge's battery backed SRAM, which has to be read and written a
+// byte at a time
+const SRAM: usize = 0x0E00_0000;
+// written before the settings so anything else in SRAM, such as a fresh cartridge, is ignored
+const MAGIC: &[u8; 4] = b"PNS1";
+
+// emulators and flash carts look for this string in the ROM to know the game saves to SRAM
+#[used]
+static SAVE_TYPE: [u8; 12] = *b"SRAM_V113\0\0\0";
+
+pub const MAX_VOLUME: u8 = 8;
+
+// the buttons which jump and attack can be moved to
+pub const REMAPPABLE_BUTTONS: [Button; 4] = [Button::A, Button::B, Button::L, Button::R];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn rules(self) -> Rules {
+        match self {
+            Difficulty::Easy => Rules {
+                invulnerability: 180,
+                hits_per_sword: 2,
+                scroll_speed: Number::new(3) / 4,
+                boss_patience: 60 * 7,
+                heal_drop_chance: 2,
+                extra_flames: 0,
+            },
+            Difficulty::Normal => Rules {
+                invulnerability: 120,
+                hits_per_sword: 1,
+                scroll_speed: 1.into(),
+                boss_patience: 60 * 5,
+                heal_drop_chance: 4,
+                extra_flames: 0,
+            },
+            Difficulty::Hard => Rules {
+                invulnerability: 90,
+                hits_per_sword: 1,
+                scroll_speed: Number::new(3) / 2,
+                boss_patience: 60 * 3,
+                heal_drop_chance: 6,
+                extra_flames: 2,
+            },
+        }
+    }
+}
+
+// the parts of the game which change with the difficulty. they are worked out when a game starts,
+// so changing the difficulty from the pause menu takes effect on the next attempt.
+#[derive(Clone, Copy)]
+pub struct Rules {
+    // how many frames the player can't be hurt for after being hit
+    pub invulnerability: u16,
+    // how many hits it takes to lose each sword
+    pub hits_per_sword: u8,
+    // multiplies how fast the screen scrolls on its own
+    pub scroll_speed: Number,
+    // how many frames the boss waits to be hit after finishing its attacks
+    pub boss_patience: u16,
+    // defeated mini flames drop health one time in this many
+    pub heal_drop_chance: i32,
+    // how many more mini flames than usual the boss throws out when it is hit
+    pub extra_flames: u8,
+}
+
+#[derive(Clone, Copy)]
+pub struct Settings {
+    // from 0 to MAX_VOLUME
+    pub music_volume: u8,
+    pub sfx_volume: u8,
+    pub screen_shake: bool,
+    // indices into REMAPPABLE_BUTTONS, which are never the same as each other
+    pub jump_button: usize,
+    pub attack_button: usize,
+    pub difficulty: Difficulty,
+    pub show_timer: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            music_volume: MAX_VOLUME,
+            sfx_volume: MAX_VOLUME,
+            screen_shake: true,
+            jump_button: 0,
+            attack_button: 1,
+            difficulty: Difficulty::Normal,
+            show_timer: false,
+        }
+    }
+}
+
+impl Settings {
+    // falls back to the defaults if nothing has been saved yet or what was saved doesn't make sense
+    pub fn load() -> Self {
+        let mut bytes = [0; MAGIC.len() + 8];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = unsafe { ((SRAM + i) as *const u8).read_volatile() };
+        }
+
+        let (magic, data) = bytes.split_at(MAGIC.len());
+        if magic != MAGIC || data[7] != checksum(&data[..7]) {
+            return Self::default();
+        }
+
+        let settings = Self {
+            music_volume: data[0].min(MAX_VOLUME),
+            sfx_volume: data[1].min(MAX_VOLUME),
+            screen_shake: data[2] != 0,
+            jump_button: data[3] as usize,
+            attack_button: data[4] as usize,
+            difficulty: match Difficulty::ALL.get(data[5] as usize) {
+                Some(&difficulty) => difficulty,
+                None => return Self::default(),
+            },
+            show_timer: data[6] != 0,
+        };
+
+        if settings.jump_button >= REMAPPABLE_BUTTONS.len()
+            || settings.attack_button >= REMAPPABLE_BUTTONS.len()
+            || settings.jump_button == settings.attack_button
+        {
+            return Self::default();
+        }
+
+        settings
+    }
+
+    pub fn save(&self) {
+        let data = [
+            self.music_volume,
+            self.sfx_volume,
+            self.screen_shake as u8,
+            self.jump_button as u8,
+            self.attack_button as u8,
+            self.difficulty as u8,
+            self.show_timer as u8,
+        ];
+
+        let bytes = MAGIC
+            .iter()
+            .chain(data.iter())
+            .copied()
+            .chain(core::iter::once(checksum(&data)));
+
+        for (i, byte) in bytes.enumerate() {
+            unsafe { ((SRAM + i) as *mut u8).write_volatile(byte) };
+        }
+    }
+
+    pub fn volume(&self) -> Volume {
+        let level = |volume: u8| Num::new(volume as i16) / MAX_VOLUME as i16;
+
+        Volume {
+            master: 1.into(),
+            music: level(self.music_volume),
+            sfx: level(self.sfx_volume),
+        }
+    }
+
+    pub fn jump(&self) -> Button {
+        REMAPPABLE_BUTTONS[self.jump_button]
+    }
+
+    pub fn attack(&self) -> Button {
+        REMAPPABLE_BUTTONS[self.attack_button]
+    }
+
+    pub fn next_difficulty(&mut self, direction: i32) {
+        let count = Difficulty::ALL.len() as i32;
+        self.difficulty =
+            Difficulty::ALL[(self.difficulty as i32 + direction).rem_euclid(count) as usize];
+    }
+
+    // moves the jump button along, swapping with the attack button if they would be the same
+    pub fn next_jump_button(&mut self, direction: i32) {
+        let count = REMAPPABLE_BUTTONS.len() as i32;
+        let jump = (self.jump_button as i32 + direction).rem_euclid(count) as usize;
+        if jump == self.attack_button {
+            self.attack_button = self.jump_button;
+        }
+        self.jump_button = jump;
+    }
+
+    pub fn next_attack_button(&mut self, direction: i32) {
+        let count = REMAPPABLE_BUTTONS.len() as i32;
+        let attack = (self.attack_button as i32 + direction).rem_euclid(count) as usize;
+        if attack == self.jump_button {
+            self.jump_button = self.attack_button;
+        }
+        self.attack_button = attack;
+    }
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter()
+        .fold(0x5a, |sum, byte| sum.rotate_left(1) ^ byte)
+}
@@ -1,156 +1,628 @@
+use core::cmp::Reverse;
+
 use super::rng::get_random;
-use agb::number::Num;
+use super::tracker::{self, ModulePlayer};
+use super::Number;
+use agb::display::{HEIGHT, WIDTH};
+use agb::number::{Num, Vector2D};
 use agb::sound::mixer::{ChannelId, Mixer, SoundChannel};
-
-const BAT_DEATH: &[u8] = agb::include_wav!("sfx/BatDeath.wav");
-const BAT_FLAP: &[u8] = agb::include_wav!("sfx/BatFlap.wav");
-const JUMP1: &[u8] = agb::include_wav!("sfx/Jump1.wav");
-const JUMP2: &[u8] = agb::include_wav!("sfx/Jump2.wav");
-const JUMP3: &[u8] = agb::include_wav!("sfx/Jump3.wav");
-const PLAYER_GETS_HIT: &[u8] = agb::include_wav!("sfx/PlayerGetsHit.wav");
-const PLAYER_HEAL: &[u8] = agb::include_wav!("sfx/PlayerHeal.wav");
-const PLAYER_LANDS: &[u8] = agb::include_wav!("sfx/PlayerLands.wav");
-const SLIME_BOING: &[u8] = agb::include_wav!("sfx/SlimeBoing.wav");
-const SLIME_DEATH: &[u8] = agb::include_wav!("sfx/SlimeDeath.wav");
-const SWORD_SWING: &[u8] = agb::include_wav!("sfx/SwordSwing.wav");
-const FLAME_CHARGE: &[u8] = agb::include_wav!("sfx/FlameCharge.wav");
-const BOSS_FLAME_MOVE: &[u8] = agb::include_wav!("sfx/FlameMove.wav");
-const BURNING_FLAME: &[u8] = agb::include_wav!("sfx/Burning.wav");
-
-const EMU_CRASH: &[u8] = agb::include_wav!("sfx/EmuCrash.wav");
-const EMU_STEP: &[u8] = agb::include_wav!("sfx/EmuStep.wav");
-const EMU_DEATH: &[u8] = agb::include_wav!("sfx/EmuDeath.wav");
+use alloc::vec::Vec;
 
 const PURPLE_NIGHT: &[u8] = agb::include_wav!("sfx/01 - The Purple Night (Main Loop).wav");
 const PURPLE_NIGHT_INTRO: &[u8] = agb::include_wav!("sfx/01 - The Purple Night (Intro).wav");
-const SUNRISE: &[u8] = agb::include_wav!("sfx/02 - Sunrise (Main Loop).wav");
-const BLANK_INTRO: &[u8] = agb::include_wav!("sfx/02 - Sunrise (Intro).wav");
 const BLUE_SPIRIT: &[u8] = agb::include_wav!("sfx/03 - Blue Spirit (Main Loop).wav");
 
+// how many samples the mixer plays each frame at 18157Hz
+pub const SAMPLES_PER_FRAME: usize = 304;
+// how many sounds the mixer can play at once, including the music
+const MIXER_CHANNELS: usize = 8;
+
+// When every channel is in use, a sound can take the channel of one with a lower priority.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Priority {
+    // things enemies do all the time which nobody will miss
+    Ambient,
+    Enemy,
+    // anything the player or boss does
+    Important,
+}
+
+// An entry in the sound bank, which build.rs generates from sfx/sounds.txt along with a SoundId
+// for each of them.
+struct Sound {
+    // one of these is picked at random each time the sound is played
+    variants: &'static [&'static [u8]],
+    // a fixed point number with 4 fractional bits
+    volume: i16,
+    // fixed point numbers with 8 fractional bits
+    playback: usize,
+    pitch_jitter: usize,
+    priority: Priority,
+    // how many copies of this sound can play at once
+    max_instances: usize,
+    // how many frames have to pass before the sound can be played again
+    cooldown: u16,
+    // whether to make the music quieter so the sound stands out
+    duck: bool,
+}
+
+include!(concat!(env!("OUT_DIR"), "/sounds.rs"));
+
+// a sound effect which is currently playing
+struct Voice {
+    sound: SoundId,
+    channel: ChannelId,
+    age: u16,
+    // how many frames the sound lasts for
+    length: u16,
+}
+
+impl Voice {
+    fn priority(&self) -> Priority {
+        SOUNDS[self.sound as usize].priority
+    }
+}
+
+// Music is either a recording, or a module which is sequenced from short samples as it plays and
+// takes up much less space.
+#[derive(Clone, Copy)]
+enum Music {
+    Sampled(&'static [u8]),
+    Sequenced(&'static tracker::Module),
+}
+
+// One layer of a track's main loop, which fades in once the game is at least as intense as the
+// given intensity. All the stems of a track play at once so they stay in sync.
+struct Stem {
+    music: Music,
+    intensity: u8,
+}
+
+struct Track {
+    intro: Option<Music>,
+    main_loop: &'static [Stem],
+}
+
+// drums join in once enemies gather or the player is down to the dagger, and a bass line once both
+// are true
+const PURPLE_NIGHT_TRACK: Track = Track {
+    intro: Some(Music::Sampled(PURPLE_NIGHT_INTRO)),
+    main_loop: &[
+        Stem {
+            music: Music::Sampled(PURPLE_NIGHT),
+            intensity: 0,
+        },
+        Stem {
+            music: Music::Sequenced(&tracker::PURPLE_NIGHT_DRUMS),
+            intensity: 1,
+        },
+        Stem {
+            music: Music::Sequenced(&tracker::PURPLE_NIGHT_BASS),
+            intensity: 2,
+        },
+    ],
+};
+// the intro is two seconds of silence, a pattern which breaks off after 16 rows at speed 6 and
+// tempo 120
+const SUNRISE_TRACK: Track = Track {
+    intro: Some(Music::Sequenced(&tracker::SUNRISE_INTRO)),
+    main_loop: &[Stem {
+        music: Music::Sequenced(&tracker::SUNRISE),
+        intensity: 0,
+    }],
+};
+// drums join the boss fight once the boss is nearly beaten or the player is down to the dagger
+const BLUE_SPIRIT_TRACK: Track = Track {
+    intro: None,
+    main_loop: &[
+        Stem {
+            music: Music::Sampled(BLUE_SPIRIT),
+            intensity: 0,
+        },
+        Stem {
+            music: Music::Sequenced(&tracker::BLUE_SPIRIT_DRUMS),
+            intensity: 3,
+        },
+    ],
+};
+
+enum PlayingMusic {
+    Sampled(ChannelId),
+    Sequenced(ModulePlayer),
+}
+
+impl PlayingMusic {
+    fn update(&mut self, mixer: &mut Mixer) {
+        if let PlayingMusic::Sequenced(player) = self {
+            player.update(mixer);
+        }
+    }
+
+    fn set_volume(&mut self, mixer: &mut Mixer, volume: Num<i16, 4>) {
+        match self {
+            PlayingMusic::Sampled(id) => {
+                if let Some(channel) = mixer.get_channel(id) {
+                    channel.volume(volume);
+                }
+            }
+            PlayingMusic::Sequenced(player) => player.set_volume(volume),
+        }
+    }
+
+    fn stop(&mut self, mixer: &mut Mixer) {
+        match self {
+            PlayingMusic::Sampled(id) => {
+                if let Some(channel) = mixer.get_channel(id) {
+                    channel.stop();
+                }
+            }
+            PlayingMusic::Sequenced(player) => player.stop(mixer),
+        }
+    }
+
+    fn channels_in_use(&self) -> usize {
+        match self {
+            PlayingMusic::Sampled(_) => 1,
+            PlayingMusic::Sequenced(player) => player.channels_in_use(),
+        }
+    }
+}
+
+struct PlayingStem {
+    music: PlayingMusic,
+    intensity: u8,
+    // how far the stem has faded in, from 0 to MUSIC_FADE_FRAMES
+    level: u16,
+}
+
+// how many frames it takes to crossfade between tracks or fade music out
+const MUSIC_FADE_FRAMES: u16 = 60;
+// how long the music stays quieter for after an important sound effect
+const DUCK_FRAMES: u16 = 40;
+// sounds further than this many pixels from the middle of the screen aren't played at all, and
+// start getting quieter once they're off screen
+const HEARING_RADIUS: i32 = 240;
+
+// the main loop of a track waiting for its intro to finish
+struct QueuedLoop {
+    main_loop: &'static [Stem],
+    // for recorded intros, since modules know when they have finished
+    samples_left: usize,
+}
+
+// the recorded stem of the main loop, which the modules playing alongside it start again with each
+// time it loops so they can never drift out of time with it
+struct LoopClock {
+    // in samples
+    length: usize,
+    played: usize,
+}
+
+// how far through a music fade this is, from 0 to 1
+fn fade_progress(time: u16) -> Num<i16, 4> {
+    Num::new(time as i16) / MUSIC_FADE_FRAMES as i16
+}
+
+#[derive(Clone, Copy)]
+pub struct Volume {
+    pub master: Num<i16, 4>,
+    pub music: Num<i16, 4>,
+    pub sfx: Num<i16, 4>,
+}
+
+impl Default for Volume {
+    fn default() -> Self {
+        Self {
+            master: 1.into(),
+            music: 1.into(),
+            sfx: 1.into(),
+        }
+    }
+}
+
 pub struct Sfx<'a> {
-    bgm: Option<ChannelId>,
+    // the stems of the current track, or just the intro
+    bgm: Vec<PlayingStem>,
+    queued_loop: Option<QueuedLoop>,
+    loop_clock: Option<LoopClock>,
+    // how long the current track has been fading in for
+    fade_in: Option<u16>,
+    // the previous track and how long it has been fading out for
+    fade_out: Option<(Vec<PlayingStem>, u16)>,
+    intensity: u8,
+    duck_time: u16,
+    volume: Volume,
+    // where the top left of the screen is, to work out where positional sounds are coming from
+    camera: Vector2D<Number>,
+    voices: Vec<Voice>,
     mixer: &'a mut Mixer,
 }
 
 impl<'a> Sfx<'a> {
-    pub fn new(mixer: &'a mut Mixer) -> Self {
-        Self { mixer, bgm: None }
+    pub fn new(mixer: &'a mut Mixer, volume: Volume) -> Self {
+        Self {
+            mixer,
+            bgm: Vec::new(),
+            queued_loop: None,
+            loop_clock: None,
+            fade_in: None,
+            fade_out: None,
+            intensity: 0,
+            duck_time: 0,
+            volume,
+            camera: (0, 0).into(),
+            voices: Vec::new(),
+        }
+    }
+
+    // the music picks up the new volume on the next frame, and sounds the next time they play
+    pub fn set_volume(&mut self, volume: Volume) {
+        self.volume = volume;
     }
 
     pub fn vblank(&mut self) {
+        let fading = self.fade_out.iter_mut().flat_map(|(fading, _)| fading);
+        for stem in self.bgm.iter_mut().chain(fading) {
+            stem.music.update(self.mixer);
+        }
+
+        // agb's mixer can't change which sample a channel is playing, so rather than carrying on
+        // in the intro's channel the loop starts on new channels. the mixer fills a whole frame of
+        // samples at once, so once the intro has played its last buffer or its module has played
+        // its last row the loop starts in the very next buffer. recorded intros are trimmed to end
+        // exactly on a buffer so there is no gap.
+        if let Some(queued_loop) = &self.queued_loop {
+            let intro_finished = match self.bgm.first().map(|intro| &intro.music) {
+                Some(PlayingMusic::Sequenced(player)) => player.is_finished(),
+                _ => queued_loop.samples_left == 0,
+            };
+
+            if intro_finished {
+                let main_loop = queued_loop.main_loop;
+                self.queued_loop = None;
+                for intro in self.bgm.iter_mut() {
+                    intro.music.stop(self.mixer);
+                }
+                self.bgm = self.play_stems(main_loop);
+                for stem in self.bgm.iter_mut() {
+                    stem.music.update(self.mixer);
+                }
+            }
+        }
+
+        self.update_music_volume();
+
         self.mixer.vblank();
-    }
 
-    pub fn stop_music(&mut self) {
-        if let Some(bgm) = &self.bgm {
-            let channel = self.mixer.get_channel(bgm).unwrap();
-            channel.stop();
+        if let Some(queued_loop) = &mut self.queued_loop {
+            queued_loop.samples_left = queued_loop.samples_left.saturating_sub(SAMPLES_PER_FRAME);
         }
-        self.bgm = None;
-    }
 
-    pub fn purple_night(&mut self) {
-        if let Some(bgm) = &self.bgm {
-            let channel = self.mixer.get_channel(bgm).unwrap();
-            channel.stop();
+        if let Some(clock) = &mut self.loop_clock {
+            clock.played += SAMPLES_PER_FRAME;
+            if clock.played >= clock.length {
+                clock.played -= clock.length;
+                for stem in self.bgm.iter_mut() {
+                    if let PlayingMusic::Sequenced(player) = &mut stem.music {
+                        player.restart();
+                    }
+                }
+            }
         }
 
-        let mut channel = SoundChannel::new_high_priority(PURPLE_NIGHT);
-        channel.stereo().should_loop();
-        self.bgm = self.mixer.play_sound(channel);
+        for voice in self.voices.iter_mut() {
+            voice.age += 1;
+        }
+        self.voices.retain(|voice| voice.age < voice.length);
     }
 
-    pub fn sunrise(&mut self) {
-        if let Some(bgm) = &self.bgm {
-            let channel = self.mixer.get_channel(bgm).unwrap();
-            channel.stop();
+    fn update_music_volume(&mut self) {
+        let mut music_volume = self.volume.master * self.volume.music;
+        if self.duck_time > 0 {
+            self.duck_time -= 1;
+            music_volume = music_volume / 2;
         }
 
-        let mut channel = SoundChannel::new_high_priority(SUNRISE);
-        channel.stereo().should_loop();
-        self.bgm = self.mixer.play_sound(channel);
-    }
+        let mut bgm_volume = music_volume;
+        if let Some(time) = &mut self.fade_in {
+            *time += 1;
+            bgm_volume = music_volume * fade_progress(*time);
+            if *time >= MUSIC_FADE_FRAMES {
+                self.fade_in = None;
+            }
+        }
 
-    pub fn boss(&mut self) {
-        if let Some(bgm) = &self.bgm {
-            let channel = self.mixer.get_channel(bgm).unwrap();
-            channel.stop();
+        for stem in self.bgm.iter_mut() {
+            if self.intensity >= stem.intensity {
+                stem.level = (stem.level + 1).min(MUSIC_FADE_FRAMES);
+            } else {
+                stem.level = stem.level.saturating_sub(1);
+            }
+
+            stem.music
+                .set_volume(self.mixer, bgm_volume * fade_progress(stem.level));
         }
 
-        let mut channel = SoundChannel::new_high_priority(BLUE_SPIRIT);
-        channel.stereo().should_loop();
-        self.bgm = self.mixer.play_sound(channel);
+        if let Some((fading, time)) = &mut self.fade_out {
+            *time += 1;
+            let finished = *time >= MUSIC_FADE_FRAMES;
+            let fade_out_volume = music_volume * (Num::new(1) - fade_progress(*time));
+
+            for stem in fading.iter_mut() {
+                if finished {
+                    stem.music.stop(self.mixer);
+                } else {
+                    stem.music
+                        .set_volume(self.mixer, fade_out_volume * fade_progress(stem.level));
+                }
+            }
+
+            if finished {
+                self.fade_out = None;
+            }
+        }
     }
 
-    pub fn jump(&mut self) {
-        let r = get_random() % 3;
+    // fades out whatever music is playing
+    pub fn stop_music(&mut self) {
+        if let Some((fading, _)) = &mut self.fade_out {
+            for stem in fading.iter_mut() {
+                stem.music.stop(self.mixer);
+            }
+        }
 
-        let channel = match r {
-            0 => SoundChannel::new(JUMP1),
-            1 => SoundChannel::new(JUMP2),
-            _ => SoundChannel::new(JUMP3),
-        };
+        self.fade_out = Some((core::mem::take(&mut self.bgm), 0));
+        self.fade_in = None;
+        self.queued_loop = None;
+        self.loop_clock = None;
+    }
 
-        self.mixer.play_sound(channel);
+    // makes the music quieter for a moment so an important sound effect stands out
+    fn duck(&mut self) {
+        self.duck_time = DUCK_FRAMES;
     }
 
-    pub fn sword(&mut self) {
-        self.mixer.play_sound(SoundChannel::new(SWORD_SWING));
+    fn play_music(&mut self, music: Music, should_loop: bool) -> Option<PlayingMusic> {
+        match music {
+            Music::Sampled(music) => {
+                let mut channel = SoundChannel::new_high_priority(music);
+                channel.stereo();
+                if should_loop {
+                    channel.should_loop();
+                }
+                self.mixer.play_sound(channel).map(PlayingMusic::Sampled)
+            }
+            Music::Sequenced(module) => Some(PlayingMusic::Sequenced(ModulePlayer::new(
+                module,
+                should_loop,
+            ))),
+        }
     }
 
-    pub fn slime_boing(&mut self) {
-        let mut channel = SoundChannel::new(SLIME_BOING);
-        let one: Num<i16, 4> = 1.into();
-        channel.volume(one / 4);
-        self.mixer.play_sound(channel);
+    // starts every stem at once, with the ones which are too intense for now silent. modules which
+    // play alongside a recording don't loop on their own, and instead start again whenever the
+    // recording does.
+    fn play_stems(&mut self, stems: &'static [Stem]) -> Vec<PlayingStem> {
+        let mut playing = Vec::with_capacity(stems.len());
+
+        self.loop_clock = stems.iter().find_map(|stem| match stem.music {
+            // the music is stereo, so there are two bytes per sample
+            Music::Sampled(samples) => Some(LoopClock {
+                length: samples.len() / 2,
+                played: 0,
+            }),
+            Music::Sequenced(_) => None,
+        });
+
+        for stem in stems {
+            let should_loop = matches!(stem.music, Music::Sampled(_)) || self.loop_clock.is_none();
+            if let Some(music) = self.play_music(stem.music, should_loop) {
+                playing.push(PlayingStem {
+                    music,
+                    intensity: stem.intensity,
+                    level: if self.intensity >= stem.intensity {
+                        MUSIC_FADE_FRAMES
+                    } else {
+                        0
+                    },
+                });
+            }
+        }
+
+        playing
     }
 
-    pub fn slime_dead(&mut self) {
-        let channel = SoundChannel::new(SLIME_DEATH);
-        self.mixer.play_sound(channel);
+    // how intense the game currently is, which decides which stems of the music can be heard
+    pub fn set_intensity(&mut self, intensity: u8) {
+        self.intensity = intensity;
     }
 
-    pub fn player_hurt(&mut self) {
-        self.mixer.play_sound(SoundChannel::new(PLAYER_GETS_HIT));
+    pub fn set_camera(&mut self, camera: Vector2D<Number>) {
+        self.camera = camera;
     }
 
-    pub fn player_heal(&mut self) {
-        self.mixer.play_sound(SoundChannel::new(PLAYER_HEAL));
+    pub fn play(&mut self, id: SoundId) {
+        self.play_voice(id, 1.into(), 0.into());
     }
 
-    pub fn player_land(&mut self) {
-        self.mixer.play_sound(SoundChannel::new(PLAYER_LANDS));
+    // pans the sound towards where it is on screen and makes it quieter the further off screen
+    // it is
+    pub fn play_at(&mut self, id: SoundId, position: Vector2D<Number>) {
+        let half_width: Number = (WIDTH / 2).into();
+        let difference = position - (self.camera + (WIDTH / 2, HEIGHT / 2).into());
+        let distance = difference.x.abs().max(difference.y.abs());
+
+        if distance > HEARING_RADIUS.into() {
+            return;
+        }
+
+        let attenuation = if distance <= half_width {
+            1.into()
+        } else {
+            Number::new(1) - (distance - half_width) / (Number::new(HEARING_RADIUS) - half_width)
+        };
+        let panning = (difference.x / half_width).clamp((-1).into(), 1.into());
+
+        self.play_voice(id, to_sound_num(attenuation), to_sound_num(panning));
     }
 
-    pub fn bat_flap(&mut self) {
-        self.mixer.play_sound(SoundChannel::new(BAT_FLAP));
+    fn play_voice(&mut self, id: SoundId, volume: Num<i16, 4>, panning: Num<i16, 4>) {
+        let sound = &SOUNDS[id as usize];
+
+        let mut instances = self.voices.iter().filter(|voice| voice.sound == id);
+        if instances.clone().count() >= sound.max_instances
+            || instances.any(|voice| voice.age < sound.cooldown)
+        {
+            return;
+        }
+
+        let music_channels: usize = self
+            .bgm
+            .iter()
+            .chain(self.fade_out.iter().flat_map(|(fading, _)| fading))
+            .map(|stem| stem.music.channels_in_use())
+            .sum();
+        if self.voices.len() + music_channels >= MIXER_CHANNELS && !self.steal_voice(sound.priority)
+        {
+            return;
+        }
+
+        let sample = sound.variants[get_random() as usize % sound.variants.len()];
+        let playback = if sound.pitch_jitter > 0 {
+            sound.playback - sound.pitch_jitter
+                + get_random() as usize % (2 * sound.pitch_jitter + 1)
+        } else {
+            sound.playback
+        };
+
+        let mut channel = SoundChannel::new(sample);
+        channel
+            .volume(volume * Num::from_raw(sound.volume) * self.volume.master * self.volume.sfx)
+            .panning(panning)
+            .playback(Num::from_raw(playback));
+
+        if sound.duck {
+            self.duck();
+        }
+
+        if let Some(channel) = self.mixer.play_sound(channel) {
+            self.voices.push(Voice {
+                sound: id,
+                channel,
+                age: 0,
+                length: (sample.len() * 256 / playback / SAMPLES_PER_FRAME + 1) as u16,
+            });
+        }
     }
 
-    pub fn bat_death(&mut self) {
-        self.mixer.play_sound(SoundChannel::new(BAT_DEATH));
+    // stops the oldest of the least important sounds to make room for one with the given
+    // priority, returning whether there was one to stop
+    fn steal_voice(&mut self, priority: Priority) -> bool {
+        let victim = self
+            .voices
+            .iter()
+            .enumerate()
+            .filter(|(_, voice)| voice.priority() < priority)
+            .min_by_key(|(_, voice)| (voice.priority(), Reverse(voice.age)))
+            .map(|(i, _)| i);
+
+        match victim {
+            Some(i) => {
+                let voice = self.voices.swap_remove(i);
+                if let Some(channel) = self.mixer.get_channel(&voice.channel) {
+                    channel.stop();
+                }
+                true
+            }
+            None => false,
+        }
     }
 
-    pub fn flame_charge(&mut self) {
-        self.mixer.play_sound(SoundChannel::new(FLAME_CHARGE));
+    // crossfades from whatever was already playing
+    fn play_track(&mut self, track: &'static Track) {
+        let crossfade = !self.bgm.is_empty();
+        self.stop_music();
+
+        match track.intro.map(align_intro) {
+            Some(intro) => {
+                self.bgm = self
+                    .play_music(intro, false)
+                    .map(|music| PlayingStem {
+                        music,
+                        intensity: 0,
+                        level: MUSIC_FADE_FRAMES,
+                    })
+                    .into_iter()
+                    .collect();
+                self.queued_loop = Some(QueuedLoop {
+                    main_loop: track.main_loop,
+                    samples_left: match intro {
+                        // the music is stereo, so there are two bytes per sample
+                        Music::Sampled(intro) => intro.len() / 2,
+                        Music::Sequenced(_) => 0,
+                    },
+                });
+            }
+            None => {
+                self.bgm = self.play_stems(track.main_loop);
+            }
+        }
+
+        if crossfade {
+            self.fade_in = Some(0);
+        }
     }
 
-    pub fn boss_move(&mut self) {
-        self.mixer.play_sound(SoundChannel::new(BOSS_FLAME_MOVE));
+    pub fn purple_night(&mut self) {
+        self.play_track(&PURPLE_NIGHT_TRACK);
     }
 
-    pub fn burning(&mut self) {
-        self.mixer.play_sound(SoundChannel::new(BURNING_FLAME));
+    pub fn sunrise(&mut self) {
+        self.play_track(&SUNRISE_TRACK);
     }
 
-    pub fn emu_step(&mut self) {
-        self.mixer.play_sound(SoundChannel::new(EMU_STEP));
+    pub fn boss(&mut self) {
+        self.play_track(&BLUE_SPIRIT_TRACK);
     }
+}
 
-    pub fn emu_crash(&mut self) {
-        self.mixer.play_sound(SoundChannel::new(EMU_CRASH));
+// Sounds only start at the beginning of a frame's buffer, so a recorded intro which ends part of
+// the way through one would leave silence before the loop can start. Instead the start is cut off
+// to make the intro a whole number of frames long, which is checked to only ever cut off silence.
+fn align_intro(intro: Music) -> Music {
+    match intro {
+        Music::Sampled(samples) => Music::Sampled(&samples[unaligned_bytes(samples)..]),
+        Music::Sequenced(module) => Music::Sequenced(module),
     }
+}
 
-    pub fn emu_death(&mut self) {
-        self.mixer.play_sound(SoundChannel::new(EMU_DEATH));
+// how many bytes at the start of a recording stop it being a whole number of frames long
+const fn unaligned_bytes(samples: &[u8]) -> usize {
+    // the music is stereo, so there are two bytes per sample
+    samples.len() / 2 % SAMPLES_PER_FRAME * 2
+}
+
+const fn intro_starts_silent(track: &Track) -> bool {
+    if let Some(Music::Sampled(samples)) = track.intro {
+        let mut i = 0;
+        while i < unaligned_bytes(samples) {
+            if samples[i] != 0 {
+                return false;
+            }
+            i += 1;
+        }
     }
+
+    true
+}
+
+const _: () = assert!(
+    intro_starts_silent(&PURPLE_NIGHT_TRACK)
+        && intro_starts_silent(&SUNRISE_TRACK)
+        && intro_starts_silent(&BLUE_SPIRIT_TRACK),
+    "recorded intros must start with silence so align_intro can cut it off"
+);
+
+fn to_sound_num(n: Number) -> Num<i16, 4> {
+    Num::from_raw((n.to_raw() >> 4) as i16)
 }
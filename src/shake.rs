@@ -0,0 +1,79 @@
+use super::Number;
+use crate::rng::get_random;
+use agb::{input::Tri, number::Vector2D};
+
+// the furthest the screen moves from where it should be at full trauma
+const MAX_OFFSET: i32 = 4;
+// how much trauma wears off each frame
+const DECAY: i32 = 20;
+
+pub struct Shake {
+    // from 0 to 1, how violently the screen is shaking. adding more trauma stacks up to 1.
+    trauma: Number,
+    // a push in one direction which springs back, rather than shaking randomly
+    kick: Vector2D<Number>,
+    enabled: bool,
+}
+
+impl Shake {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            trauma: 0.into(),
+            kick: (0, 0).into(),
+            enabled,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn add_trauma(&mut self, trauma: Number) {
+        self.trauma = (self.trauma + trauma).min(1.into());
+    }
+
+    pub fn kick(&mut self, direction: Vector2D<Number>) {
+        self.kick += direction;
+    }
+
+    pub fn player_hurt(&mut self) {
+        self.add_trauma(1.into());
+    }
+
+    pub fn emu_crash(&mut self, direction: Tri) {
+        self.add_trauma(Number::new(1) / 4);
+        self.kick((Number::new(direction as i32) * 3, 0.into()).into());
+    }
+
+    pub fn boss_explode(&mut self) {
+        self.add_trauma(1.into());
+        self.kick((0, 4).into());
+    }
+
+    pub fn update(&mut self) {
+        self.trauma = (self.trauma - Number::new(1) / DECAY).max(0.into());
+        self.kick = self.kick * 3 / 4;
+    }
+
+    pub fn offset(&self) -> Vector2D<Number> {
+        if !self.enabled {
+            return (0, 0).into();
+        }
+
+        self.jitter(self.trauma * MAX_OFFSET) + self.kick
+    }
+
+    // a random offset of up to magnitude pixels, for shaking a single sprite rather than the
+    // whole screen
+    pub fn jitter(&self, magnitude: Number) -> Vector2D<Number> {
+        if !self.enabled || magnitude == 0.into() {
+            return (0, 0).into();
+        }
+
+        (
+            Number::from_raw(get_random()).rem_euclid(magnitude) - magnitude / 2,
+            Number::from_raw(get_random()).rem_euclid(magnitude) - magnitude / 2,
+        )
+            .into()
+    }
+}
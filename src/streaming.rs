@@ -0,0 +1,162 @@
+use core::ops::Range;
+
+use super::Number;
+use agb::display::background::BackgroundRegular;
+use agb::number::Vector2D;
+
+// the hardware map of a regular background is 32x32 tiles and wraps around as it scrolls
+const HARDWARE_SIZE: i32 = 32;
+// the screen needs 31 columns and 21 rows of tiles when it isn't lined up with the tiles
+const VISIBLE_COLUMNS: i32 = 31;
+const VISIBLE_ROWS: i32 = 21;
+// How many tiles each stream may copy in a frame. Copying a tile is a lookup in the map in ROM and
+// a write to VRAM, which comes to roughly 60 cycles, so 64 tiles take about 3 of the 228 scanlines
+// in a frame for each layer. That is enough for a whole column and a whole row of the hardware
+// map, so a layer scrolling diagonally by up to 8 pixels a frame never falls behind.
+const TILE_BUDGET: i32 = 2 * HARDWARE_SIZE;
+
+// Copies a map of any size into the hardware map of a background a strip of tiles at a time as
+// they come into view, rather than relying on the whole map fitting in the hardware map. agb is
+// never given the map, so the tiles are written one at a time through the background.
+pub struct TileStream {
+    map: &'static [u16],
+    width: i32,
+    height: i32,
+
+    background: BackgroundRegular<'static>,
+
+    // the columns and rows of the map which are currently in the hardware map
+    columns: Range<i32>,
+    rows: Range<i32>,
+}
+
+impl TileStream {
+    pub fn new(
+        background: BackgroundRegular<'static>,
+        map: &'static [u16],
+        width: u32,
+        height: u32,
+        position: Vector2D<Number>,
+    ) -> Self {
+        let mut stream = Self {
+            map,
+            width: width as i32,
+            height: height as i32,
+            background,
+            columns: 0..0,
+            rows: 0..0,
+        };
+
+        stream.load_all(position.floor());
+        stream
+    }
+
+    pub fn update(&mut self, position: Vector2D<Number>) {
+        let position = position.floor();
+        let (wanted_columns, wanted_rows) = Self::wanted(position);
+
+        // anything moving too far in one frame, such as a respawn, just redraws everything
+        if !overlaps(&self.columns, &wanted_columns) || !overlaps(&self.rows, &wanted_rows) {
+            self.load_all(position);
+            return;
+        }
+
+        let mut budget = TILE_BUDGET;
+
+        while budget >= self.rows.len() as i32 && self.columns.end < wanted_columns.end {
+            self.copy_column(self.columns.end);
+            self.columns.end += 1;
+            if self.columns.len() as i32 > HARDWARE_SIZE {
+                self.columns.start += 1;
+            }
+            budget -= self.rows.len() as i32;
+        }
+
+        while budget >= self.rows.len() as i32 && self.columns.start > wanted_columns.start {
+            self.columns.start -= 1;
+            self.copy_column(self.columns.start);
+            if self.columns.len() as i32 > HARDWARE_SIZE {
+                self.columns.end -= 1;
+            }
+            budget -= self.rows.len() as i32;
+        }
+
+        while budget >= self.columns.len() as i32 && self.rows.end < wanted_rows.end {
+            self.copy_row(self.rows.end);
+            self.rows.end += 1;
+            if self.rows.len() as i32 > HARDWARE_SIZE {
+                self.rows.start += 1;
+            }
+            budget -= self.columns.len() as i32;
+        }
+
+        while budget >= self.columns.len() as i32 && self.rows.start > wanted_rows.start {
+            self.rows.start -= 1;
+            self.copy_row(self.rows.start);
+            if self.rows.len() as i32 > HARDWARE_SIZE {
+                self.rows.end -= 1;
+            }
+            budget -= self.columns.len() as i32;
+        }
+
+        self.set_scroll(position);
+    }
+
+    fn wanted(position: Vector2D<i32>) -> (Range<i32>, Range<i32>) {
+        let first_column = position.x.div_euclid(8);
+        let first_row = position.y.div_euclid(8);
+
+        (
+            first_column..first_column + VISIBLE_COLUMNS,
+            first_row..first_row + VISIBLE_ROWS,
+        )
+    }
+
+    fn load_all(&mut self, position: Vector2D<i32>) {
+        let (columns, rows) = Self::wanted(position);
+        self.columns = columns;
+        self.rows = rows;
+
+        for x in self.columns.clone() {
+            self.copy_column(x);
+        }
+
+        self.set_scroll(position);
+    }
+
+    fn copy_column(&mut self, x: i32) {
+        for y in self.rows.clone() {
+            self.copy_tile(x, y);
+        }
+    }
+
+    fn copy_row(&mut self, y: i32) {
+        for x in self.columns.clone() {
+            self.copy_tile(x, y);
+        }
+    }
+
+    fn copy_tile(&mut self, x: i32, y: i32) {
+        let tile = if x < 0 || x >= self.width || y < 0 || y >= self.height {
+            0
+        } else {
+            self.map[(self.width * y + x) as usize]
+        };
+
+        let hardware_position = (
+            x.rem_euclid(HARDWARE_SIZE) as u16,
+            y.rem_euclid(HARDWARE_SIZE) as u16,
+        );
+        self.background.set_tile(hardware_position.into(), tile);
+    }
+
+    // agb wraps the position around the hardware map in the same way the stream does
+    fn set_scroll(&mut self, position: Vector2D<i32>) {
+        self.background.set_position(position);
+        self.background.commit();
+    }
+}
+
+fn overlaps(a: &Range<i32>, b: &Range<i32>) -> bool {
+    a.start < b.end && b.start < a.end
+}
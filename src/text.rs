@@ -0,0 +1,179 @@
+use agb::display::background::{BackgroundDistributor, BackgroundRegular};
+use agb::display::palette16::Palette16;
+use agb::display::Priority;
+use agb::number::Vector2D;
+use alloc::vec::Vec;
+
+// the text is drawn on its own background above everything else, with its own palette and tiles
+// after the ones the level uses. build.rs checks that the level's tiles fit below the font.
+pub const PALETTE: u16 = 15;
+const FONT_TILE_START: u16 = 448;
+
+pub const COLUMNS: usize = 30;
+pub const ROWS: usize = 20;
+
+const FONT_PALETTE: Palette16 =
+    Palette16::new([0, 0x7fff, 0x2807, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+// the characters in the font, after a blank tile used for spaces and anything not in the font
+const CHARACTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789:<>-.";
+
+// each glyph is 5x7 pixels, with one row per byte and the leftmost pixel in bit 4
+#[rustfmt::skip]
+const FONT: [[u8; 7]; 41] = [
+    [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001], // A
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110], // B
+    [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110], // C
+    [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110], // D
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111], // E
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000], // F
+    [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111], // G
+    [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001], // H
+    [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110], // I
+    [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100], // J
+    [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001], // K
+    [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111], // L
+    [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001], // M
+    [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001], // N
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110], // O
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000], // P
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101], // Q
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001], // R
+    [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110], // S
+    [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100], // T
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110], // U
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100], // V
+    [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010], // W
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001], // X
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100], // Y
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111], // Z
+    [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110], // 0
+    [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110], // 1
+    [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111], // 2
+    [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110], // 3
+    [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010], // 4
+    [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110], // 5
+    [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110], // 6
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000], // 7
+    [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110], // 8
+    [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100], // 9
+    [0b00000, 0b00100, 0b00100, 0b00000, 0b00100, 0b00100, 0b00000], // :
+    [0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010], // <
+    [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000], // >
+    [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000], // -
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100], // .
+];
+
+// Loads the level's tiles followed by the font, all at once so that agb puts them both where the
+// backgrounds expect them.
+pub fn load_tiles(background_distributor: &mut BackgroundDistributor, level_tiles: &[u32]) {
+    assert!(
+        level_tiles.len() <= FONT_TILE_START as usize * 8,
+        "the level's tiles must fit below the font"
+    );
+
+    let blank = [0; 7];
+    let mut tiles = Vec::with_capacity((FONT_TILE_START as usize + FONT.len() + 1) * 8);
+    tiles.extend_from_slice(level_tiles);
+    tiles.resize(FONT_TILE_START as usize * 8, 0);
+    for glyph in core::iter::once(&blank).chain(FONT.iter()) {
+        tiles.extend_from_slice(&glyph_tile(glyph));
+    }
+
+    background_distributor.set_background_tilemap(0, &tiles);
+}
+
+pub struct TextLayer {
+    background: BackgroundRegular<'static>,
+}
+
+impl TextLayer {
+    // The font must have been loaded with load_tiles, and the level's palettes must leave the
+    // last one free for it.
+    pub fn new(
+        mut background: BackgroundRegular<'static>,
+        background_distributor: &mut BackgroundDistributor,
+    ) -> Self {
+        background.set_position(Vector2D::new(0, 0));
+        background.set_priority(Priority::P0);
+        background.commit();
+        background.show();
+
+        background_distributor.set_background_palette(PALETTE as u8, &FONT_PALETTE);
+
+        let mut text = Self { background };
+        text.clear();
+        text
+    }
+
+    pub fn clear(&mut self) {
+        for y in 0..ROWS {
+            self.write(0, y, "                              ");
+        }
+    }
+
+    // writes the text starting at the given tile, cutting it off at the edge of the screen
+    pub fn write(&mut self, x: usize, y: usize, text: &str) {
+        if y >= ROWS {
+            return;
+        }
+
+        for (i, character) in text.chars().take(COLUMNS.saturating_sub(x)).enumerate() {
+            let glyph = CHARACTERS
+                .find(character.to_ascii_uppercase())
+                .map_or(0, |glyph| glyph as u16 + 1);
+
+            self.background.set_tile(
+                ((x + i) as u16, y as u16).into(),
+                (FONT_TILE_START + glyph) | (PALETTE << 12),
+            );
+        }
+    }
+}
+
+// the longest time format_time can write, which is the most minutes that fit in a u32 followed by
+// the seconds
+pub const TIME_LENGTH: usize = 11;
+
+// writes the seconds as minutes and seconds, such as 12:05, into the end of the buffer rather than
+// allocating a string for it every frame
+pub fn format_time(seconds: u32, buffer: &mut [u8; TIME_LENGTH]) -> &str {
+    let mut start = buffer.len() - 3;
+    buffer[start..].copy_from_slice(&[
+        b':',
+        b'0' + (seconds % 60 / 10) as u8,
+        b'0' + (seconds % 10) as u8,
+    ]);
+
+    let mut minutes = seconds / 60;
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (minutes % 10) as u8;
+        minutes /= 10;
+        if minutes == 0 {
+            break;
+        }
+    }
+
+    core::str::from_utf8(&buffer[start..]).unwrap()
+}
+
+// draws the glyph in colour 1 with a shadow in colour 2 below and to the right of it, as rows of
+// 4 bit pixels with the leftmost pixel in the lowest bits
+fn glyph_tile(glyph: &[u8; 7]) -> [u32; 8] {
+    let mut tile = [0; 8];
+
+    for (offset, colour) in [(1, 2), (0, 1)] {
+        for (y, &row) in glyph.iter().enumerate() {
+            for x in 0..5 {
+                if row & (1 << (4 - x)) != 0 {
+                    let shift = (x + 1 + offset) * 4;
+                    let pixel = &mut tile[y + offset];
+                    *pixel = (*pixel & !(0xf << shift)) | (colour << shift);
+                }
+            }
+        }
+    }
+
+    tile
+}
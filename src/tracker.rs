@@ -0,0 +1,274 @@
+use agb::number::Num;
+use agb::sound::mixer::{ChannelId, Mixer, SoundChannel};
+
+use crate::sfx::SAMPLES_PER_FRAME;
+
+const CHANNELS: usize = 4;
+const ROWS: usize = 64;
+
+// modules tick tempo * 2 / 5 times a second, so adding the tempo each frame there is a tick every
+// time the progress reaches 149.32 frames (in 8 bit fixed point)
+const TICK_LENGTH: u32 = 38226;
+
+// the Amiga plays the outer channels on the left and the inner ones on the right. this only pans
+// them half way so it isn't too tiring on headphones.
+const PANNING: [i16; CHANNELS] = [-8, 8, 8, -8];
+
+pub struct Module {
+    instruments: &'static [Instrument],
+    // the patterns to play, in order
+    order: &'static [u8],
+    patterns: &'static [[[Note; CHANNELS]; ROWS]],
+}
+
+struct Instrument {
+    // played once at the start of each note
+    attack: &'static [u8],
+    // then played on repeat until the note ends, unless it is empty
+    sustain: &'static [u8],
+    volume: u8,
+    // divided by the period of a note to give its playback speed with 8 fractional bits
+    rate: u32,
+}
+
+struct Note {
+    // how long each cycle of the note lasts in Amiga clock ticks, or 0 to keep playing the last
+    // note
+    period: u16,
+    // starting from 1, or 0 to keep using the last instrument
+    instrument: u8,
+    effect: u8,
+    parameter: u8,
+}
+
+include!(concat!(env!("OUT_DIR"), "/modules.rs"));
+
+#[derive(Default)]
+struct Channel {
+    playing: Option<ChannelId>,
+    instrument: Option<&'static Instrument>,
+    // from 0 to 64
+    volume: u8,
+    // the playback speed of the note with 8 fractional bits
+    playback: usize,
+    // how many frames until the attack finishes and the sustain should start
+    attack_frames: usize,
+}
+
+impl Channel {
+    fn play(&mut self, mixer: &mut Mixer, period: u16, panning: i16) {
+        let instrument = match self.instrument {
+            Some(instrument) => instrument,
+            None => return,
+        };
+
+        self.playback = (instrument.rate / period as u32) as usize;
+
+        if instrument.attack.is_empty() {
+            self.start(mixer, instrument.sustain, true, panning);
+            self.attack_frames = 0;
+        } else {
+            self.start(mixer, instrument.attack, false, panning);
+            self.attack_frames =
+                instrument.attack.len() * 256 / self.playback.max(1) / SAMPLES_PER_FRAME;
+        }
+    }
+
+    fn start(&mut self, mixer: &mut Mixer, sample: &'static [u8], should_loop: bool, panning: i16) {
+        self.stop(mixer);
+
+        if sample.is_empty() {
+            return;
+        }
+
+        let mut channel = SoundChannel::new_high_priority(sample);
+        channel
+            .playback(Num::from_raw(self.playback))
+            .panning(Num::from_raw(panning))
+            .volume(0.into());
+        if should_loop {
+            channel.should_loop();
+        }
+
+        self.playing = mixer.play_sound(channel);
+    }
+
+    fn update(&mut self, mixer: &mut Mixer, volume: Num<i16, 4>, panning: i16) {
+        if self.attack_frames > 0 {
+            self.attack_frames -= 1;
+
+            if self.attack_frames == 0 {
+                if let Some(instrument) = self.instrument {
+                    self.start(mixer, instrument.sustain, true, panning);
+                }
+            }
+        }
+
+        if let Some(channel) = self.playing.as_ref().and_then(|id| mixer.get_channel(id)) {
+            channel.volume(volume * self.volume as i16 / 64);
+        }
+    }
+
+    fn stop(&mut self, mixer: &mut Mixer) {
+        if let Some(channel) = self.playing.take().and_then(|id| mixer.get_channel(&id)) {
+            channel.stop();
+        }
+    }
+}
+
+// Plays a module a tick at a time, using one mixer channel for each of its channels. Only the
+// effects which change the volume, speed and position in the song are supported.
+pub struct ModulePlayer {
+    module: &'static Module,
+    channels: [Channel; CHANNELS],
+    should_loop: bool,
+    finished: bool,
+    volume: Num<i16, 4>,
+
+    position: usize,
+    row: usize,
+    tick: u8,
+    // how many ticks there are in a row
+    speed: u8,
+    tempo: u8,
+    tick_progress: u32,
+    // where to go after this row, if somewhere other than the next one
+    jump: Option<(usize, usize)>,
+}
+
+impl ModulePlayer {
+    pub fn new(module: &'static Module, should_loop: bool) -> Self {
+        Self {
+            module,
+            channels: Default::default(),
+            should_loop,
+            finished: module.order.is_empty(),
+            volume: 1.into(),
+
+            position: 0,
+            row: 0,
+            tick: 0,
+            speed: 6,
+            tempo: 125,
+            // start straight away rather than waiting for the first tick
+            tick_progress: TICK_LENGTH,
+            jump: None,
+        }
+    }
+
+    // goes back to the start of the song, so a module can keep time with a recording by starting
+    // again whenever the recording loops
+    pub fn restart(&mut self) {
+        self.finished = self.module.order.is_empty();
+        self.position = 0;
+        self.row = 0;
+        self.tick = 0;
+        self.tick_progress = TICK_LENGTH;
+        self.jump = None;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn set_volume(&mut self, volume: Num<i16, 4>) {
+        self.volume = volume;
+    }
+
+    // how many mixer channels the module is currently using
+    pub fn channels_in_use(&self) -> usize {
+        self.channels
+            .iter()
+            .filter(|channel| channel.playing.is_some())
+            .count()
+    }
+
+    pub fn update(&mut self, mixer: &mut Mixer) {
+        if self.finished {
+            return;
+        }
+
+        while self.tick_progress >= TICK_LENGTH && !self.finished {
+            self.tick_progress -= TICK_LENGTH;
+            self.tick(mixer);
+        }
+        self.tick_progress += self.tempo as u32 * 256;
+
+        for (channel, &panning) in self.channels.iter_mut().zip(PANNING.iter()) {
+            channel.update(mixer, self.volume, panning);
+        }
+    }
+
+    pub fn stop(&mut self, mixer: &mut Mixer) {
+        for channel in self.channels.iter_mut() {
+            channel.stop(mixer);
+        }
+    }
+
+    fn tick(&mut self, mixer: &mut Mixer) {
+        if self.tick == 0 {
+            self.play_row(mixer);
+        }
+
+        self.tick += 1;
+        if self.tick >= self.speed {
+            self.tick = 0;
+            self.next_row(mixer);
+        }
+    }
+
+    fn play_row(&mut self, mixer: &mut Mixer) {
+        let module = self.module;
+        let pattern = &module.patterns[module.order[self.position] as usize];
+
+        for (i, note) in pattern[self.row].iter().enumerate() {
+            let channel = &mut self.channels[i];
+
+            if note.instrument != 0 {
+                channel.instrument = module.instruments.get(note.instrument as usize - 1);
+                channel.volume = channel.instrument.map_or(0, |instrument| instrument.volume);
+            }
+
+            if note.period != 0 {
+                channel.play(mixer, note.period, PANNING[i]);
+            }
+
+            match (note.effect, note.parameter) {
+                // position jump
+                (0xb, position) => self.jump = Some((position as usize, 0)),
+                // set volume
+                (0xc, volume) => channel.volume = volume.min(64),
+                // pattern break, where the row is written in decimal
+                (0xd, row) => {
+                    let row = (row >> 4) as usize * 10 + (row & 0xf) as usize;
+                    self.jump = Some((self.position + 1, row.min(ROWS - 1)));
+                }
+                // set speed or tempo
+                (0xf, speed @ 1..=31) => self.speed = speed,
+                (0xf, tempo @ 32..=255) => self.tempo = tempo,
+                _ => {}
+            }
+        }
+    }
+
+    fn next_row(&mut self, mixer: &mut Mixer) {
+        let (mut position, row) = match self.jump.take() {
+            Some(jump) => jump,
+            None if self.row + 1 < ROWS => (self.position, self.row + 1),
+            None => (self.position + 1, 0),
+        };
+
+        if position >= self.module.order.len() {
+            if !self.should_loop {
+                self.finished = true;
+                self.stop(mixer);
+                return;
+            }
+
+            position = 0;
+        }
+
+        self.position = position;
+        self.row = row;
+    }
+}